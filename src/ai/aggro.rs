@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+/// Per enemy def tuning for when it notices the player
+#[derive(Deserialize, Clone, Debug)]
+pub struct AggroDef {
+    pub detection_radius: f32,
+    //Other idle enemies this close get pulled in when this one notices the player
+    pub alert_radius: f32,
+}
+
+impl Default for AggroDef {
+    fn default() -> Self {
+        Self {
+            detection_radius: 500.0,
+            alert_radius: 400.0,
+        }
+    }
+}
+
+/// Enemies start out `AiStage::Idle` wandering around where they spawned until they see the
+/// player, get hit or get alerted by a neighbour
+#[derive(Component)]
+pub struct Aggro {
+    pub detection_radius: f32,
+    pub alert_radius: f32,
+    pub home: Vec3,
+    pub wander_target: Option<Vec3>,
+    pub wander_timer: Timer,
+}
+
+impl Aggro {
+    pub fn new(def: &AggroDef, home: Vec3) -> Self {
+        Self {
+            detection_radius: def.detection_radius,
+            alert_radius: def.alert_radius,
+            home,
+            wander_target: None,
+            wander_timer: Timer::from_seconds(2.5, true),
+        }
+    }
+}
+
+/// Sent when an enemy notices the player so idle enemies nearby join in
+#[derive(Clone, Copy)]
+pub struct AlertEvent {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+/// Only this many enemies are allowed to be charging or attacking at once, the rest hang around
+/// in range until one finishes
+pub struct AttackTokens {
+    pub max: usize,
+    pub holders: HashSet<Entity>,
+}
+
+impl Default for AttackTokens {
+    fn default() -> Self {
+        Self {
+            max: 2,
+            holders: HashSet::default(),
+        }
+    }
+}
+
+impl AttackTokens {
+    /// True if this enemy already has a token or there was a free one to take
+    pub fn try_take(&mut self, ent: Entity) -> bool {
+        if self.holders.contains(&ent) {
+            return true;
+        }
+        if self.holders.len() < self.max {
+            self.holders.insert(ent);
+            return true;
+        }
+        false
+    }
+}
+
+const WANDER_RADIUS: f32 = 150.0;
+const WANDER_SPEED_SCALE: f32 = 0.4;
+
+pub fn aggro_detection(
+    mut enemies: Query<(&Aggro, &Health, &mut AiStage, &Transform), Without<Player>>,
+    player: Query<&Transform, With<Player>>,
+    grid: Option<Res<NavGrid>>,
+    mut alerts: EventReader<AlertEvent>,
+    mut new_alerts: EventWriter<AlertEvent>,
+) {
+    let player = match player.get_single() {
+        Ok(player) => player.translation,
+        Err(_) => return,
+    };
+    let alerts: Vec<AlertEvent> = alerts.iter().copied().collect();
+    for (aggro, health, mut stage, transform) in &mut enemies {
+        if !matches!(*stage, AiStage::Idle) {
+            continue;
+        }
+        let position = transform.translation;
+        let sees_player = position.truncate().distance(player.truncate()) < aggro.detection_radius
+            && grid.as_ref().map_or(true, |grid| {
+                grid.line_of_sight(position.truncate(), player.truncate())
+            });
+        let alerted = alerts
+            .iter()
+            .any(|alert| alert.position.truncate().distance(position.truncate()) < alert.radius);
+        if sees_player || alerted || health.flashing {
+            *stage = AiStage::GetInRange;
+            new_alerts.send(AlertEvent {
+                position,
+                radius: aggro.alert_radius,
+            });
+        }
+    }
+}
+
+pub fn idle_wander(
+    mut enemies: Query<(&Enemy, &mut Aggro, &AiStage, &mut Transform)>,
+    grid: Option<Res<NavGrid>>,
+    time: Res<GameTime>,
+) {
+    for (enemy, mut aggro, stage, mut transform) in &mut enemies {
+        if !matches!(*stage, AiStage::Idle) {
+            continue;
+        }
+        aggro.wander_timer.tick(time.delta());
+        if aggro.wander_timer.just_finished() {
+            let mut rng = rand::thread_rng();
+            let target = aggro.home
+                + Vec3::new(
+                    rng.gen_range(-WANDER_RADIUS..WANDER_RADIUS),
+                    rng.gen_range(-WANDER_RADIUS..WANDER_RADIUS),
+                    0.0,
+                );
+            //Just stand still this time if it would walk into a wall
+            let clear = grid.as_ref().map_or(true, |grid| {
+                grid.line_of_sight(transform.translation.truncate(), target.truncate())
+            });
+            aggro.wander_target = if clear { Some(target) } else { None };
+        }
+        if let Some(target) = aggro.wander_target {
+            let direction = (target - transform.translation).truncate();
+            if direction.length() < 5.0 {
+                aggro.wander_target = None;
+            } else {
+                transform.translation += direction.normalize().extend(0.0)
+                    * enemy.speed
+                    * WANDER_SPEED_SCALE
+                    * time.delta_seconds();
+            }
+        }
+    }
+}
+
+pub fn release_attack_tokens(mut tokens: ResMut<AttackTokens>, stages: Query<&AiStage>) {
+    //Dead, despawned or finished attacking all give the token back
+    tokens.holders.retain(|ent| {
+        matches!(
+            stages.get(*ent),
+            Ok(AiStage::Charge(..) | AiStage::Attack(..) | AiStage::CoolDown(..))
+        )
+    });
+}
@@ -0,0 +1,56 @@
+use crate::prelude::*;
+
+/// Turtle style attack, picks a direction when the attack starts and doesn't turn
+#[derive(Component, Default)]
+pub struct Charger {
+    pub locked: Option<Vec3>,
+}
+
+pub fn charge_behaviour(
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &Navigation,
+            &mut Charger,
+            &mut AiStage,
+            &mut Transform,
+        ),
+        Without<Player>,
+    >,
+    player: Query<&Transform, With<Player>>,
+    mut tokens: ResMut<AttackTokens>,
+    time: Res<GameTime>,
+) {
+    if let Ok(player) = player.get_single() {
+        for (ent, enemy, nav, mut charger, mut stage, mut transform) in &mut enemies {
+            match *stage {
+                AiStage::GetInRange => {
+                    if step_toward_player(
+                        &mut transform,
+                        enemy,
+                        nav,
+                        player.translation,
+                        time.delta_seconds(),
+                    ) {
+                        start_charge(&mut stage, enemy, ent, &mut tokens);
+                    }
+                }
+                AiStage::Attack(..) => {
+                    let direction = *charger
+                        .locked
+                        .get_or_insert_with(|| dash_direction(&transform, player.translation));
+                    dash(
+                        &mut transform,
+                        direction,
+                        enemy.attack_speed,
+                        time.delta_seconds(),
+                    );
+                }
+                _ => {
+                    charger.locked = None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::prelude::*;
+
+/// Stops approaching and backs away from the player once badly hurt, it still fights back if
+/// it's already mid attack
+#[derive(Component)]
+pub struct FleeWhenHurt {
+    pub health_below: f32,
+    pub speed: f32,
+}
+
+pub fn flee_behaviour(
+    mut enemies: Query<(&FleeWhenHurt, &Health, &mut AiStage, &mut Transform), Without<Player>>,
+    player: Query<&Transform, With<Player>>,
+    time: Res<GameTime>,
+) {
+    if let Ok(player) = player.get_single() {
+        for (flee, health, mut stage, mut transform) in &mut enemies {
+            if health.health > flee.health_below {
+                //Healed back up, go back to fighting
+                if matches!(*stage, AiStage::Flee) {
+                    *stage = AiStage::GetInRange;
+                }
+                continue;
+            }
+            if !matches!(*stage, AiStage::GetInRange | AiStage::Flee) {
+                continue;
+            }
+            *stage = AiStage::Flee;
+            let away = (transform.translation - player.translation)
+                .truncate()
+                .normalize_or_zero();
+            transform.translation += away.extend(0.0) * flee.speed * time.delta_seconds();
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::prelude::*;
+
+/// Frog style movement, sits still for `wait_time` then jumps toward the player for `jump_time`
+#[derive(Component)]
+pub struct Hopper {
+    pub jumping: bool,
+    pub timer: Timer,
+}
+
+pub fn hop_behaviour(
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &Navigation,
+            &mut Hopper,
+            &mut AiStage,
+            &mut Transform,
+        ),
+        Without<Player>,
+    >,
+    player: Query<&Transform, With<Player>>,
+    mut tokens: ResMut<AttackTokens>,
+    mut sfx: EventWriter<SfxEvent>,
+    time: Res<GameTime>,
+) {
+    if let Ok(player) = player.get_single() {
+        for (ent, enemy, nav, mut hopper, mut stage, mut transform) in &mut enemies {
+            match *stage {
+                AiStage::GetInRange => {
+                    hopper.timer.tick(time.delta());
+                    if hopper.jumping {
+                        if step_toward_player(
+                            &mut transform,
+                            enemy,
+                            nav,
+                            player.translation,
+                            time.delta_seconds(),
+                        ) {
+                            start_charge(&mut stage, enemy, ent, &mut tokens);
+                        } else if hopper.timer.just_finished() {
+                            hopper.jumping = false;
+                            hopper.timer = Timer::from_seconds(enemy.wait_time, false);
+                            sfx.send(SfxEvent(Sfx::FrogLand));
+                        }
+                    } else if hopper.timer.just_finished() {
+                        hopper.jumping = true;
+                        sfx.send(SfxEvent(Sfx::FrogJump));
+                        hopper.timer = Timer::from_seconds(enemy.jump_time, false);
+                    }
+                }
+                AiStage::Attack(..) => {
+                    let direction = dash_direction(&transform, player.translation);
+                    dash(
+                        &mut transform,
+                        direction,
+                        enemy.attack_speed,
+                        time.delta_seconds(),
+                    );
+                }
+                _ => {}
+            }
+            //Always land and sit for a bit after attacking
+            if !matches!(*stage, AiStage::GetInRange) && hopper.jumping {
+                hopper.jumping = false;
+                sfx.send(SfxEvent(Sfx::FrogLand));
+                hopper.timer = Timer::from_seconds(enemy.wait_time, false);
+            }
+        }
+    }
+}
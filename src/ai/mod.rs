@@ -0,0 +1,158 @@
+use bevy::ecs::system::EntityCommands;
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+mod aggro;
+mod charge;
+mod flee;
+mod hop;
+mod spit;
+mod swoop;
+mod walk;
+
+pub use aggro::*;
+pub use charge::*;
+pub use flee::*;
+pub use hop::*;
+pub use spit::*;
+pub use swoop::*;
+pub use walk::*;
+
+pub struct AiPlugin;
+
+/// One piece of enemy behaviour listed in an `EnemyDef`, each one becomes its own component
+/// driven by its own system so new patterns never have to touch the existing ones.
+/// Movement behaviours only act during the `AiStage`s they care about, `enemy_attack` still owns
+/// the timers that move an enemy from charging to attacking to cooling down
+#[derive(Deserialize, Clone, Debug)]
+pub enum AiBehaviour {
+    //Walks straight at the player and dashes straight at them
+    Walk,
+    //Waits then jumps toward the player in bursts
+    Hop,
+    //Curves through the player when attacking, arc is the total turn in radians
+    Swoop { arc: f32 },
+    //Locks on when the attack starts and barrels straight ahead
+    Charge,
+    //Runs away instead of approaching once health drops low enough
+    FleeWhenHurt { health_below: f32, speed: f32 },
+    //Hangs back at target_offset and shoots instead of dashing
+    Spit { projectile_speed: f32, damage: f32 },
+}
+
+impl AiBehaviour {
+    pub fn insert(&self, enemy: &mut EntityCommands, stats: &Enemy) {
+        match self {
+            AiBehaviour::Walk => {
+                enemy.insert(Walker);
+            }
+            AiBehaviour::Hop => {
+                enemy.insert(Hopper {
+                    jumping: false,
+                    timer: Timer::from_seconds(stats.wait_time, false),
+                });
+            }
+            AiBehaviour::Swoop { arc } => {
+                enemy.insert(Swooper {
+                    arc: *arc,
+                    side: if rand::random() { 1.0 } else { -1.0 },
+                });
+            }
+            AiBehaviour::Charge => {
+                enemy.insert(Charger::default());
+            }
+            AiBehaviour::FleeWhenHurt {
+                health_below,
+                speed,
+            } => {
+                enemy.insert(FleeWhenHurt {
+                    health_below: *health_below,
+                    speed: *speed,
+                });
+            }
+            AiBehaviour::Spit {
+                projectile_speed,
+                damage,
+            } => {
+                enemy.insert(Spitter {
+                    projectile_speed: *projectile_speed,
+                    damage: *damage,
+                    fired: false,
+                });
+            }
+        }
+    }
+}
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AttackTokens::default())
+            .add_event::<AlertEvent>()
+            .add_system(aggro_detection)
+            .add_system(idle_wander)
+            .add_system(release_attack_tokens)
+            .add_system(walk_behaviour)
+            .add_system(hop_behaviour)
+            .add_system(swoop_behaviour)
+            .add_system(charge_behaviour)
+            //Picks Flee before anything else gets to step toward the player this frame
+            .add_system(
+                flee_behaviour
+                    .before(walk_behaviour)
+                    .before(hop_behaviour)
+                    .before(swoop_behaviour)
+                    .before(charge_behaviour)
+                    .before(spit_behaviour),
+            )
+            .add_system(spit_behaviour);
+    }
+}
+
+const TOLERANCE: f32 = 1.0;
+
+/// Moves toward the spot `target_offset` away from the player, following the nav path if there
+/// is a wall in the way. Returns true once it's there and can actually reach the player
+pub fn step_toward_player(
+    transform: &mut Transform,
+    enemy: &Enemy,
+    nav: &Navigation,
+    player: Vec3,
+    delta_seconds: f32,
+) -> bool {
+    if let Some(waypoint) = nav.waypoint {
+        let direction = (waypoint - transform.translation)
+            .truncate()
+            .normalize_or_zero();
+        transform.translation += direction.extend(0.0) * enemy.speed * delta_seconds;
+        return false;
+    }
+    let player_dir = (player - transform.translation).normalize_or_zero();
+    let target = player - player_dir * enemy.target_offset;
+    let direction = target - transform.translation;
+    if direction.length_squared() > TOLERANCE {
+        transform.translation += direction.normalize() * enemy.speed * delta_seconds;
+        false
+    } else {
+        nav.can_reach
+    }
+}
+
+/// Attack movement, keeps enemies on their layer while they fly around
+pub fn dash(transform: &mut Transform, direction: Vec3, speed: f32, delta_seconds: f32) {
+    transform.translation += direction.normalize_or_zero() * speed * delta_seconds;
+    transform.translation.z = 10.0;
+}
+
+/// Which way to dash to hit the player, nudged a little so enemies never sit dead center
+pub fn dash_direction(transform: &Transform, player: Vec3) -> Vec3 {
+    player - transform.translation + Vec3::new(3.0, 0.0, 0.0)
+}
+
+/// Starts winding up an attack if there's an attack token free, otherwise the enemy just keeps
+/// waiting in range
+pub fn start_charge(stage: &mut AiStage, enemy: &Enemy, ent: Entity, tokens: &mut AttackTokens) {
+    if tokens.try_take(ent) {
+        *stage = AiStage::Charge(Timer::from_seconds(enemy.charge_time, false));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::prelude::*;
+
+/// Keeps its distance and spits a projectile at the player instead of dashing in
+#[derive(Component)]
+pub struct Spitter {
+    pub projectile_speed: f32,
+    pub damage: f32,
+    //Already spat this attack
+    pub fired: bool,
+}
+
+//Backs off if the player gets closer than this fraction of target_offset
+const TOO_CLOSE: f32 = 0.6;
+
+pub fn spit_behaviour(
+    mut commands: Commands,
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &Navigation,
+            &mut Spitter,
+            &mut AiStage,
+            &mut Transform,
+        ),
+        Without<Player>,
+    >,
+    player: Query<&Transform, With<Player>>,
+    mut tokens: ResMut<AttackTokens>,
+    time: Res<GameTime>,
+) {
+    if let Ok(player) = player.get_single() {
+        for (ent, enemy, nav, mut spitter, mut stage, mut transform) in &mut enemies {
+            match *stage {
+                AiStage::GetInRange => {
+                    let to_player = player.translation - transform.translation;
+                    if nav.can_reach && to_player.length() < enemy.target_offset * TOO_CLOSE {
+                        let away = -to_player.truncate().normalize_or_zero();
+                        transform.translation +=
+                            away.extend(0.0) * enemy.speed * time.delta_seconds();
+                    } else if step_toward_player(
+                        &mut transform,
+                        enemy,
+                        nav,
+                        player.translation,
+                        time.delta_seconds(),
+                    ) {
+                        start_charge(&mut stage, enemy, ent, &mut tokens);
+                    }
+                }
+                AiStage::Attack(..) => {
+                    if !spitter.fired {
+                        spitter.fired = true;
+                        let direction = dash_direction(&transform, player.translation)
+                            .truncate()
+                            .normalize_or_zero();
+                        spawn_projectile(
+                            &mut commands,
+                            ent,
+                            transform.translation,
+                            direction * spitter.projectile_speed,
+                            spitter.damage,
+                        );
+                    }
+                }
+                _ => {
+                    spitter.fired = false;
+                }
+            }
+        }
+    }
+}
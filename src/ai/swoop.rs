@@ -0,0 +1,62 @@
+use crate::prelude::*;
+
+/// Bat style attack, instead of dashing straight in it curves through the player
+#[derive(Component)]
+pub struct Swooper {
+    //Total turn over the whole attack in radians
+    pub arc: f32,
+    //Which way to curve, 1 or -1
+    pub side: f32,
+}
+
+pub fn swoop_behaviour(
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &Navigation,
+            &Swooper,
+            &mut AiStage,
+            &mut Transform,
+        ),
+        Without<Player>,
+    >,
+    player: Query<&Transform, With<Player>>,
+    mut tokens: ResMut<AttackTokens>,
+    time: Res<GameTime>,
+) {
+    if let Ok(player) = player.get_single() {
+        for (ent, enemy, nav, swooper, mut stage, mut transform) in &mut enemies {
+            let attack_progress = match &*stage {
+                AiStage::Attack(timer) => timer.percent(),
+                _ => 0.0,
+            };
+            match *stage {
+                AiStage::GetInRange => {
+                    if step_toward_player(
+                        &mut transform,
+                        enemy,
+                        nav,
+                        player.translation,
+                        time.delta_seconds(),
+                    ) {
+                        start_charge(&mut stage, enemy, ent, &mut tokens);
+                    }
+                }
+                AiStage::Attack(..) => {
+                    //Starts angled off to one side and sweeps across to the other
+                    let angle = swooper.side * swooper.arc * (0.5 - attack_progress);
+                    let direction = Quat::from_axis_angle(Vec3::Z, angle)
+                        * dash_direction(&transform, player.translation);
+                    dash(
+                        &mut transform,
+                        direction,
+                        enemy.attack_speed,
+                        time.delta_seconds(),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::prelude::*;
+
+#[derive(Component)]
+pub struct Walker;
+
+pub fn walk_behaviour(
+    mut enemies: Query<
+        (Entity, &Enemy, &Navigation, &mut AiStage, &mut Transform),
+        (With<Walker>, Without<Player>),
+    >,
+    player: Query<&Transform, With<Player>>,
+    mut tokens: ResMut<AttackTokens>,
+    time: Res<GameTime>,
+) {
+    if let Ok(player) = player.get_single() {
+        for (ent, enemy, nav, mut stage, mut transform) in &mut enemies {
+            match *stage {
+                AiStage::GetInRange => {
+                    if step_toward_player(
+                        &mut transform,
+                        enemy,
+                        nav,
+                        player.translation,
+                        time.delta_seconds(),
+                    ) {
+                        start_charge(&mut stage, enemy, ent, &mut tokens);
+                    }
+                }
+                AiStage::Attack(..) => {
+                    let direction = dash_direction(&transform, player.translation);
+                    dash(
+                        &mut transform,
+                        direction,
+                        enemy.attack_speed,
+                        time.delta_seconds(),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
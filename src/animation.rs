@@ -1,3 +1,5 @@
+use serde::Deserialize;
+
 use crate::prelude::*;
 
 pub struct AnimationPlugin;
@@ -8,18 +10,51 @@ pub struct Animation {
     pub timer: Timer,
 }
 
+/// A list of atlas indices to step through
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct Frames {
+    pub frames: Vec<usize>,
+    //Stop on the last frame instead of looping
+    #[serde(default)]
+    pub hold: bool,
+}
+
+/// Which frames an enemy shows in each `AiStage`, comes from its `EnemyDef`
+#[derive(Component, Deserialize, Clone, Debug)]
+pub struct EnemyAnimations {
+    pub frame_time: f32,
+    //Flip the sprite so it always looks at the player
+    pub face_player: bool,
+    pub idle: Frames,
+    //Falls back to idle if the enemy doesn't jump
+    #[serde(default)]
+    pub jump: Option<Frames>,
+    pub charge: Frames,
+    pub attack: Frames,
+    pub dying: Frames,
+}
+
+impl EnemyAnimations {
+    pub fn for_stage(&self, stage: &AiStage, jumping: bool) -> &Frames {
+        match stage {
+            AiStage::GetInRange if jumping => self.jump.as_ref().unwrap_or(&self.idle),
+            AiStage::Charge(_) => &self.charge,
+            AiStage::Attack(_) => &self.attack,
+            AiStage::Dieing(_) => &self.dying,
+            _ => &self.idle,
+        }
+    }
+}
+
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(animate_frog)
-            .add_system(animate_bat)
-            .add_system(animate_turtle)
-            .add_system(animate_player);
+        app.add_system(animate_enemies).add_system(animate_player);
     }
 }
 
 fn animate_player(
     mut player: Query<(&mut TextureAtlasSprite, &mut Animation, &Player)>,
-    time: Res<Time>,
+    time: Res<GameTime>,
 ) {
     if let Ok((mut sprite, mut animation, player)) = player.get_single_mut() {
         if player.roll_direction != Vec3::ZERO {
@@ -54,162 +89,47 @@ fn animate_player(
     }
 }
 
-fn animate_frog(
-    mut frogs: Query<(
-        &mut TextureAtlasSprite,
-        &AiStage,
-        &mut Animation,
-        &EnemyType,
-        &GlobalTransform,
-    )>,
-    player: Query<&GlobalTransform, With<Player>>,
-    time: Res<Time>,
-) {
-    if let Ok(player) = player.get_single() {
-        for (mut sprite, stage, mut animation, enemy, transform) in &mut frogs {
-            if !matches!(enemy, EnemyType::Frog) {
-                continue;
-            }
-            sprite.flip_x = player.translation().x - transform.translation().x > 1.0;
-
-            match stage.clone() {
-                AiStage::Jumping(_) => {
-                    sprite.index = 5;
-                }
-                AiStage::Charge(_) => {
-                    animation.timer.tick(time.delta());
-                    if animation.timer.just_finished() {
-                        if sprite.index == 3 {
-                            sprite.index = 4;
-                        } else {
-                            sprite.index = 3;
-                        }
-                    }
-                }
-                AiStage::Dieing(_) => {
-                    sprite.index = 7;
-                }
-                AiStage::Attack(_) => {
-                    if sprite.index == 2 || sprite.index == 6 {
-                        animation.timer.tick(time.delta());
-                        if animation.timer.just_finished() {
-                            sprite.index = 6;
-                        }
-                    } else {
-                        sprite.index = 2;
-                    }
-                }
-                _ => {
-                    animation.timer.tick(time.delta());
-                    if animation.timer.just_finished() {
-                        if sprite.index == 0 {
-                            sprite.index = 1;
-                        } else {
-                            sprite.index = 0;
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
-fn animate_turtle(
-    mut bats: Query<(
+fn animate_enemies(
+    mut enemies: Query<(
         &mut TextureAtlasSprite,
         &AiStage,
         &mut Animation,
-        &EnemyType,
+        &EnemyAnimations,
         &GlobalTransform,
+        Option<&Hopper>,
     )>,
     player: Query<&GlobalTransform, With<Player>>,
-    time: Res<Time>,
+    time: Res<GameTime>,
 ) {
     if let Ok(player) = player.get_single() {
-        for (mut sprite, stage, mut animation, enemy, transform) in &mut bats {
-            if !matches!(enemy, EnemyType::Turtle) {
-                continue;
+        for (mut sprite, stage, mut animation, animations, transform, hopper) in &mut enemies {
+            if animations.face_player {
+                sprite.flip_x = player.translation().x - transform.translation().x > 1.0;
             }
-            sprite.flip_x = player.translation().x - transform.translation().x > 1.0;
-
-            match stage.clone() {
-                AiStage::Charge(_) => {
-                    animation.timer.tick(time.delta());
-                    if animation.timer.just_finished() {
-                        if sprite.index == 2 {
-                            sprite.index = 3;
-                        } else {
-                            sprite.index = 2;
-                        }
-                    }
-                }
-                AiStage::Dieing(_) => {
-                    sprite.index = 5;
-                }
-                AiStage::Attack(_) => {
-                    sprite.index = 4;
-                }
-                _ => {
-                    animation.timer.tick(time.delta());
-                    if animation.timer.just_finished() {
-                        if sprite.index == 0 {
-                            sprite.index = 1;
-                        } else {
-                            sprite.index = 0;
-                        }
-                    }
-                }
-            }
-        }
-    }
-}
 
-fn animate_bat(
-    mut bats: Query<(
-        &mut TextureAtlasSprite,
-        &AiStage,
-        &mut Animation,
-        &EnemyType,
-        &GlobalTransform,
-    )>,
-    player: Query<&GlobalTransform, With<Player>>,
-    time: Res<Time>,
-) {
-    if let Ok(player) = player.get_single() {
-        for (mut sprite, stage, mut animation, enemy, transform) in &mut bats {
-            if !matches!(enemy, EnemyType::Bat) {
+            let jumping = hopper.map_or(false, |hopper| hopper.jumping);
+            let frames = animations.for_stage(stage, jumping);
+            if frames.frames.is_empty() {
                 continue;
             }
-            match stage.clone() {
-                AiStage::Charge(_) => {
-                    animation.timer.tick(time.delta());
-                    if animation.timer.just_finished() {
-                        if sprite.index == 4 {
-                            sprite.index = 5;
-                        } else if sprite.index == 5 {
-                            sprite.index = 6;
-                        } else {
-                            sprite.index = 4;
-                        }
-                    }
-                }
-                AiStage::Dieing(_) => {
-                    sprite.index = 7;
-                }
-                AiStage::Attack(_) => {
-                    sprite.index = 6;
-                }
-                _ => {
-                    animation.timer.tick(time.delta());
-                    if animation.timer.just_finished() {
-                        if sprite.index == 0 {
-                            sprite.index = 1;
-                        } else if sprite.index == 1 {
-                            sprite.index = 2;
-                        } else {
-                            sprite.index = 0;
-                        }
-                    }
+            //Just switched animations, start from the top
+            let current = match frames.frames.iter().position(|i| *i == sprite.index) {
+                Some(current) => current,
+                None => {
+                    animation.current_frame = 0;
+                    sprite.index = frames.frames[0];
+                    continue;
                 }
+            };
+            animation.timer.tick(time.delta());
+            if animation.timer.just_finished() {
+                let next = if frames.hold {
+                    (current + 1).min(frames.frames.len() - 1)
+                } else {
+                    (current + 1) % frames.frames.len()
+                };
+                animation.current_frame = next;
+                sprite.index = frames.frames[next];
             }
         }
     }
@@ -0,0 +1,176 @@
+use bevy::transform::TransformSystem;
+use rand::Rng;
+
+use crate::prelude::*;
+
+pub struct CameraControllerPlugin;
+
+/// Drives the game camera, it chases a focus point near the player instead of sitting on them
+#[derive(Component)]
+pub struct CameraController {
+    //How fast the camera catches up, higher is snappier
+    pub smoothing: f32,
+    //How far toward the mouse the camera leans, as a fraction of the distance to it
+    pub look_ahead: f32,
+    pub max_look_ahead: f32,
+    //Projection scale while a boss is alive, zooming out so the whole fight fits
+    pub boss_zoom: f32,
+    pub zoom_speed: f32,
+    //Half the size of the area the view has to stay inside
+    pub bounds: Vec2,
+    //Where the camera is before shake gets added on top
+    pub focus: Vec2,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self {
+            smoothing: 8.0,
+            look_ahead: 0.2,
+            max_look_ahead: 150.0,
+            boss_zoom: 1.3,
+            zoom_speed: 1.5,
+            bounds: RoomSize::default().camera_bounds(),
+            focus: Vec2::ZERO,
+        }
+    }
+}
+
+/// Trauma based screen shake, anything can `add_trauma` and the shake is trauma squared so
+/// small hits barely move the screen while big ones stack up into a proper rattle
+#[derive(Component)]
+pub struct CameraShake {
+    pub trauma: f32,
+    //Trauma lost per second
+    pub decay: f32,
+    pub max_offset: f32,
+    //Radians
+    pub max_roll: f32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.5,
+            max_offset: 30.0,
+            max_roll: 0.05,
+        }
+    }
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_camera)
+            //PostUpdate so room changes this frame have already moved the player
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera_follows_player.before(TransformSystem::TransformPropagate),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                camera_shake
+                    .after(camera_follows_player)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+fn spawn_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(Camera2dBundle {
+            projection: game_projection(),
+            ..default()
+        })
+        .insert(CameraController::default())
+        .insert(CameraShake::default());
+}
+
+fn camera_follows_player(
+    player_query: Query<&Transform, With<Player>>,
+    mut camera_query: Query<
+        (&mut CameraController, &mut OrthographicProjection),
+        (With<Camera2d>, Without<Player>),
+    >,
+    boss: Query<&Health, With<Boss>>,
+    room: Option<Res<RoomSize>>,
+    map: Res<MapDesc>,
+    mut last_room: Local<Option<(usize, usize)>>,
+    mouse: Res<MousePos>,
+    time: Res<Time>,
+) {
+    let player = match player_query.get_single() {
+        Ok(player) => player.translation.truncate(),
+        //Between runs, snap again once the next one starts
+        Err(_) => {
+            *last_room = None;
+            return;
+        }
+    };
+    let (mut controller, mut ortho) = match camera_query.get_single_mut() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    let dt = time.delta_seconds();
+    if let Some(room) = room {
+        controller.bounds = room.camera_bounds();
+    }
+
+    let boss_alive = boss.iter().any(|health| health.health > 0.0);
+    let zoom = if boss_alive {
+        controller.boss_zoom
+    } else {
+        1.0
+    };
+    ortho.scale += (zoom - ortho.scale) * (controller.zoom_speed * dt).min(1.0);
+
+    let look_ahead =
+        ((**mouse - player) * controller.look_ahead).clamp_length_max(controller.max_look_ahead);
+    let view = view_half_size(&ortho);
+    let target = Vec2::new(
+        clamp_camera_axis(player.x + look_ahead.x, view.x, controller.bounds.x),
+        clamp_camera_axis(player.y + look_ahead.y, view.y, controller.bounds.y),
+    );
+
+    //Don't sweep across the map when the player is teleported into a new room
+    if *last_room != Some(map.room()) {
+        *last_room = Some(map.room());
+        controller.focus = target;
+    } else {
+        //Framerate independent lerp
+        let t = 1.0 - (-controller.smoothing * dt).exp();
+        controller.focus = controller.focus.lerp(target, t);
+    }
+}
+
+/// Keeps the view inside the room, centering it instead if the window is wider than the room
+fn clamp_camera_axis(position: f32, half_view: f32, half_room: f32) -> f32 {
+    if half_view >= half_room {
+        0.0
+    } else {
+        position.clamp(-half_room + half_view, half_room - half_view)
+    }
+}
+
+fn camera_shake(
+    mut camera_query: Query<(&mut Transform, &CameraController, &mut CameraShake)>,
+    time: Res<Time>,
+) {
+    for (mut transform, controller, mut shake) in &mut camera_query {
+        shake.trauma = (shake.trauma - shake.decay * time.delta_seconds()).max(0.0);
+        let amount = shake.trauma * shake.trauma;
+        let mut rng = rand::thread_rng();
+        transform.translation.x =
+            controller.focus.x + rng.gen_range(-1.0..1.0) * amount * shake.max_offset;
+        transform.translation.y =
+            controller.focus.y + rng.gen_range(-1.0..1.0) * amount * shake.max_offset;
+        transform.rotation =
+            Quat::from_rotation_z(rng.gen_range(-1.0..1.0) * amount * shake.max_roll);
+    }
+}
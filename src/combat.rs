@@ -0,0 +1,272 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::prelude::*;
+
+pub struct CombatPlugin;
+
+//How fast the game runs while hit-stopped, not quite 0 so things still creep along
+pub const HIT_STOP_SCALE: f32 = 0.05;
+
+/// Time that gameplay should tick with instead of `Time`, it gets scaled down during hit-stop so
+/// every hit briefly freezes the action
+#[derive(Default)]
+pub struct GameTime {
+    delta: Duration,
+    hit_stop_remaining: f32,
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn scale(&self) -> f32 {
+        if self.hit_stop_remaining > 0.0 {
+            HIT_STOP_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// Freeze the game for a moment, overlapping hit-stops don't stack they just take the longest
+    pub fn hit_stop(&mut self, seconds: f32) {
+        self.hit_stop_remaining = self.hit_stop_remaining.max(seconds);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum DamageKind {
+    Slash,
+    Contact,
+    Projectile,
+    //Hurts and also poisons the target
+    Poison,
+    //Ticks from status effects, these skip i-frames and knockback
+    Status,
+}
+
+/// Anything that wants to hurt something sends one of these, `apply_damage` is the only place
+/// health actually changes
+#[derive(Clone, Debug)]
+pub struct DamageEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+    //Speed the target gets pushed away from the source at
+    pub knockback: f32,
+}
+
+/// Sent once when something's health first hits 0
+#[derive(Clone, Copy, Debug)]
+pub struct DeathEvent(pub Entity);
+
+/// Damage multipliers per kind, missing kinds take full damage
+#[derive(Component, Default, Clone)]
+pub struct Resistances(pub HashMap<DamageKind, f32>);
+
+impl Resistances {
+    pub fn multiplier(&self, kind: DamageKind) -> f32 {
+        *self.0.get(&kind).unwrap_or(&1.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    Poison,
+}
+
+#[derive(Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub damage_per_tick: f32,
+    pub tick: Timer,
+    pub remaining: Timer,
+}
+
+impl StatusEffect {
+    pub fn poison() -> Self {
+        Self {
+            kind: StatusKind::Poison,
+            damage_per_tick: 2.0,
+            tick: Timer::from_seconds(0.5, true),
+            remaining: Timer::from_seconds(3.0, false),
+        }
+    }
+}
+
+#[derive(Component, Default, Clone)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameTime::default())
+            .add_event::<DamageEvent>()
+            .add_event::<DeathEvent>()
+            //PreUpdate so every gameplay system sees the same delta this frame
+            .add_system_to_stage(CoreStage::PreUpdate, update_game_time)
+            .add_system(tick_status_effects)
+            //PostUpdate so everything that sent damage this frame gets applied this frame
+            .add_system_to_stage(CoreStage::PostUpdate, apply_damage);
+    }
+}
+
+fn update_game_time(
+    time: Res<Time>,
+    mut game_time: ResMut<GameTime>,
+    mut physics_time: ResMut<PhysicsTime>,
+) {
+    //Hit-stop counts down in real time or it would never end
+    game_time.hit_stop_remaining = (game_time.hit_stop_remaining - time.delta_seconds()).max(0.0);
+    let scale = game_time.scale();
+    game_time.delta = time.delta().mul_f32(scale);
+    physics_time.set_scale(scale);
+}
+
+/// Pushes a body directly away from `from`, strength is the starting speed
+pub fn apply_knockback(velocity: &mut Velocity, from: Vec3, to: Vec3, strength: f32) {
+    let direction = (to - from).truncate().normalize_or_zero();
+    velocity.linear = direction.extend(0.0) * strength;
+}
+
+fn tick_status_effects(
+    mut targets: Query<(Entity, &mut StatusEffects)>,
+    mut damage: EventWriter<DamageEvent>,
+    time: Res<GameTime>,
+) {
+    for (ent, mut effects) in &mut targets {
+        for effect in effects.0.iter_mut() {
+            effect.remaining.tick(time.delta());
+            effect.tick.tick(time.delta());
+            if effect.tick.just_finished() {
+                damage.send(DamageEvent {
+                    source: ent,
+                    target: ent,
+                    amount: effect.damage_per_tick,
+                    kind: DamageKind::Status,
+                    knockback: 0.0,
+                });
+            }
+        }
+        effects.0.retain(|effect| !effect.remaining.finished());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_damage(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    mut targets: Query<(
+        &mut Health,
+        &GlobalTransform,
+        Option<&Resistances>,
+        Option<&mut StatusEffects>,
+        Option<&mut Velocity>,
+        Option<&Player>,
+    )>,
+    sources: Query<&GlobalTransform>,
+    mut deaths: EventWriter<DeathEvent>,
+    mut camera: Query<&mut CameraShake>,
+    mut game_time: ResMut<GameTime>,
+    mut damage_numbers: EventWriter<DamageNumberEvent>,
+    mut particles: EventWriter<ParticleEvent>,
+    mut sfx: EventWriter<SfxEvent>,
+) {
+    for event in events.iter() {
+        let (mut health, transform, resistances, status, velocity, player) =
+            match targets.get_mut(event.target) {
+                Ok(target) => target,
+                Err(_) => continue,
+            };
+        //Already dead, let the death play out
+        if health.health <= 0.0 {
+            continue;
+        }
+        let is_status = matches!(event.kind, DamageKind::Status);
+        if !is_status && health.invulnerable > 0.0 {
+            continue;
+        }
+
+        let amount = event.amount * resistances.map_or(1.0, |r| r.multiplier(event.kind));
+        health.health -= amount;
+        health.flashing = true;
+        health.damage_flash_timer.reset();
+        if !is_status {
+            health.invulnerable = health.iframe_time;
+        }
+        if health.health <= 0.0 {
+            deaths.send(DeathEvent(event.target));
+        }
+
+        //Another poison hit refreshes the one already running instead of stacking
+        if matches!(event.kind, DamageKind::Poison) {
+            match status {
+                Some(mut status) => {
+                    match status.0.iter_mut().find(|e| e.kind == StatusKind::Poison) {
+                        Some(poison) => poison.remaining.reset(),
+                        None => status.0.push(StatusEffect::poison()),
+                    }
+                }
+                None => {
+                    commands
+                        .entity(event.target)
+                        .insert(StatusEffects(vec![StatusEffect::poison()]));
+                }
+            }
+        }
+
+        let position = transform.translation();
+        if !is_status {
+            if let (Some(mut velocity), Ok(source)) = (velocity, sources.get(event.source)) {
+                apply_knockback(
+                    &mut velocity,
+                    source.translation(),
+                    position,
+                    event.knockback,
+                );
+            }
+            //Getting hit should feel a lot worse than hitting
+            let (stop, trauma) = if player.is_some() {
+                (0.12, 0.6)
+            } else {
+                (0.05, 0.25)
+            };
+            game_time.hit_stop(stop);
+            sfx.send(SfxEvent(if player.is_some() {
+                Sfx::PlayerHit
+            } else {
+                Sfx::Hit
+            }));
+            for mut camera_shake in &mut camera {
+                camera_shake.add_trauma(trauma);
+            }
+        }
+
+        let color = match (event.kind, player.is_some()) {
+            (DamageKind::Poison | DamageKind::Status, _) => Color::LIME_GREEN,
+            (_, true) => Color::RED,
+            (_, false) => Color::WHITE,
+        };
+        damage_numbers.send(DamageNumberEvent {
+            position,
+            amount,
+            color,
+        });
+        if !is_status {
+            particles.send(ParticleEvent {
+                position,
+                color: if player.is_some() {
+                    Color::RED
+                } else {
+                    Color::rgb(0.9, 0.9, 0.6)
+                },
+                count: if player.is_some() { 12 } else { 8 },
+                speed: 300.0,
+            });
+        }
+    }
+}
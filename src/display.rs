@@ -0,0 +1,106 @@
+use bevy::{render::camera::ScalingMode, window::WindowMode};
+
+use crate::prelude::*;
+
+pub struct DisplayPlugin;
+
+/// How much pixel sized UI is scaled by, 1.0 at the original `HEIGHT` tall window
+pub struct UiScaling(pub f32);
+
+/// Base size of a UI node at `HEIGHT`, pixel values get scaled with the window and percents are
+/// left alone
+#[derive(Component)]
+pub struct ScaledSize(pub Size<Val>);
+
+/// Base font size of every section of a text at `HEIGHT`
+#[derive(Component)]
+pub struct ScaledFont(pub f32);
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(UiScaling(1.0))
+            .add_system(toggle_fullscreen)
+            .add_system(apply_fullscreen)
+            .add_system(update_ui_scaling)
+            .add_system(scale_ui_nodes.after(update_ui_scaling))
+            .add_system(scale_ui_fonts.after(update_ui_scaling));
+    }
+}
+
+/// The camera always shows `HEIGHT` world units vertically, so a bigger window shows bigger
+/// pixels rather than more of the room
+pub fn game_projection() -> OrthographicProjection {
+    OrthographicProjection {
+        scaling_mode: ScalingMode::FixedVertical(HEIGHT),
+        ..Camera2dBundle::default().projection
+    }
+}
+
+/// Half the width and height of the world the camera can currently see
+pub fn view_half_size(ortho: &OrthographicProjection) -> Vec2 {
+    Vec2::new(ortho.right - ortho.left, ortho.top - ortho.bottom) * ortho.scale / 2.0
+}
+
+fn toggle_fullscreen(keyboard: Res<Input<KeyCode>>, mut video: ResMut<VideoSettings>) {
+    if keyboard.just_pressed(KeyCode::F11) {
+        video.fullscreen = !video.fullscreen;
+    }
+}
+
+fn apply_fullscreen(video: Res<VideoSettings>, mut windows: ResMut<Windows>) {
+    if !video.is_changed() {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_mode(if video.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        });
+    }
+}
+
+fn update_ui_scaling(windows: Res<Windows>, mut scaling: ResMut<UiScaling>) {
+    if let Some(window) = windows.get_primary() {
+        let scale = window.height() / HEIGHT;
+        //Minimized windows report a height of 0, and only touch it on a real change so the ui
+        //isn't rescaled every frame
+        if window.height() > 0.0 && scale != scaling.0 {
+            scaling.0 = scale;
+        }
+    }
+}
+
+fn scale_val(val: Val, scale: f32) -> Val {
+    match val {
+        Val::Px(px) => Val::Px(px * scale),
+        other => other,
+    }
+}
+
+fn scale_ui_nodes(
+    scaling: Res<UiScaling>,
+    mut nodes: Query<(&ScaledSize, ChangeTrackers<ScaledSize>, &mut Style)>,
+) {
+    for (size, tracker, mut style) in &mut nodes {
+        if scaling.is_changed() || tracker.is_added() {
+            style.size = Size::new(
+                scale_val(size.0.width, scaling.0),
+                scale_val(size.0.height, scaling.0),
+            );
+        }
+    }
+}
+
+fn scale_ui_fonts(
+    scaling: Res<UiScaling>,
+    mut texts: Query<(&ScaledFont, ChangeTrackers<ScaledFont>, &mut Text)>,
+) {
+    for (font, tracker, mut text) in &mut texts {
+        if scaling.is_changed() || tracker.is_added() {
+            for section in &mut text.sections {
+                section.style.font_size = font.0 * scaling.0;
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::prelude::*;
+
+pub struct DoorPlugin;
+
+//Seconds for a gate to slide fully closed or open
+const GATE_TIME: f32 = 0.4;
+const GATE_COLOR: Color = Color::rgb(0.35, 0.2, 0.1);
+
+/// On an exit while it's a wall, walking into it does nothing
+#[derive(Component)]
+pub struct Locked;
+
+/// Wall across a locked exit, only exists while the exit is locked
+#[derive(Component)]
+pub struct ExitBlocker;
+
+/// The bars drawn across a doorway, slides shut while the exit is locked
+#[derive(Component)]
+pub struct ExitGate {
+    //Full size of the gate when it's closed
+    pub size: Vec2,
+    //0 is open, 1 is closed
+    pub progress: f32,
+    pub closed: bool,
+}
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::Main)
+                .with_system(lock_doors)
+                .with_system(animate_gates),
+        );
+    }
+}
+
+/// Adds an open gate across the doorway of an exit, `size` covers the whole doorway
+pub fn spawn_exit_gate(parent: &mut ChildBuilder, size: Vec2) {
+    parent
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: GATE_COLOR,
+                custom_size: Some(Vec2::ZERO),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        })
+        .insert(ExitGate {
+            size,
+            progress: 0.0,
+            closed: false,
+        })
+        .insert(Name::new("ExitGate"));
+}
+
+/// Exits get a wall across them while any enemy in the room is still alive, the exit sensor
+/// itself never changes
+fn lock_doors(
+    mut commands: Commands,
+    exits: Query<(Entity, &Children, &CollisionShape, Option<&Locked>), With<ExitDirection>>,
+    mut gates: Query<&mut ExitGate>,
+    blockers: Query<(), With<ExitBlocker>>,
+    enemies: RoomEnemies,
+    settings: Res<GameplaySettings>,
+) {
+    let lock = settings.lock_doors && !enemies.is_empty();
+    for (exit, children, shape, locked) in &exits {
+        if locked.is_some() == lock {
+            continue;
+        }
+        if lock {
+            commands
+                .entity(exit)
+                .insert(Locked)
+                .with_children(|parent| spawn_exit_blocker(parent, shape.clone()));
+        } else {
+            commands.entity(exit).remove::<Locked>();
+        }
+        for child in children {
+            if blockers.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+            if let Ok(mut gate) = gates.get_mut(*child) {
+                gate.closed = lock;
+            }
+        }
+    }
+}
+
+/// Solid wall covering the whole exit sensor, so the player can't walk into it
+fn spawn_exit_blocker(parent: &mut ChildBuilder, shape: CollisionShape) {
+    parent
+        .spawn_bundle(SpatialBundle::default())
+        .insert(shape)
+        .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::World))
+        .insert(RigidBody::Static)
+        .insert(ExitBlocker)
+        .insert(Name::new("ExitBlocker"));
+}
+
+fn animate_gates(mut gates: Query<(&mut ExitGate, &mut Sprite)>, time: Res<Time>) {
+    for (mut gate, mut sprite) in &mut gates {
+        let target = if gate.closed { 1.0 } else { 0.0 };
+        let step = time.delta_seconds() / GATE_TIME;
+        gate.progress = if gate.progress < target {
+            (gate.progress + step).min(target)
+        } else {
+            (gate.progress - step).max(target)
+        };
+        //Grows out from the middle of the doorway along the wall
+        let scale = if gate.size.x > gate.size.y {
+            Vec2::new(gate.progress, 1.0)
+        } else {
+            Vec2::new(1.0, gate.progress)
+        };
+        sprite.custom_size = Some(gate.size * scale);
+    }
+}
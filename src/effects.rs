@@ -0,0 +1,145 @@
+use rand::Rng;
+
+use crate::prelude::*;
+
+pub struct EffectsPlugin;
+
+/// Pops a floating number up from `position` that drifts up and fades out
+#[derive(Clone)]
+pub struct DamageNumberEvent {
+    pub position: Vec3,
+    pub amount: f32,
+    pub color: Color,
+}
+
+/// Sprays a handful of little square sprites out from `position`
+#[derive(Clone)]
+pub struct ParticleEvent {
+    pub position: Vec3,
+    pub color: Color,
+    pub count: usize,
+    pub speed: f32,
+}
+
+#[derive(Component)]
+pub struct DamageNumber {
+    pub timer: Timer,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub timer: Timer,
+}
+
+//Above the enemies and the player but below the screen fade
+const EFFECT_Z: f32 = 50.0;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageNumberEvent>()
+            .add_event::<ParticleEvent>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Main)
+                    .with_system(spawn_damage_numbers)
+                    .with_system(spawn_particles),
+            )
+            .add_system(update_damage_numbers)
+            .add_system(update_particles);
+    }
+}
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut events: EventReader<DamageNumberEvent>,
+    assets: Res<GameAssets>,
+) {
+    for event in events.iter() {
+        let mut rng = rand::thread_rng();
+        //Jitter a bit so multi hits don't stack perfectly on top of each other
+        let offset = Vec3::new(rng.gen_range(-20.0..20.0), 40.0, 0.0);
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    format!("{}", event.amount.round() as i32),
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 48.0,
+                        color: event.color,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(
+                    event.position.truncate().extend(EFFECT_Z) + offset,
+                ),
+                ..default()
+            })
+            .insert(DamageNumber {
+                timer: Timer::from_seconds(0.8, false),
+            })
+            .insert(RoomMember)
+            .insert(Name::new("DamageNumber"));
+    }
+}
+
+fn update_damage_numbers(
+    mut commands: Commands,
+    mut numbers: Query<(Entity, &mut DamageNumber, &mut Transform, &mut Text)>,
+    time: Res<GameTime>,
+) {
+    for (ent, mut number, mut transform, mut text) in &mut numbers {
+        number.timer.tick(time.delta());
+        transform.translation.y += 80.0 * time.delta_seconds();
+        let alpha = number.timer.percent_left();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+        if number.timer.just_finished() {
+            commands.entity(ent).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_particles(mut commands: Commands, mut events: EventReader<ParticleEvent>) {
+    for event in events.iter() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..event.count {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = event.speed * rng.gen_range(0.4..1.0);
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: event.color,
+                        custom_size: Some(Vec2::splat(rng.gen_range(4.0..9.0))),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        event.position.truncate().extend(EFFECT_Z),
+                    ),
+                    ..default()
+                })
+                .insert(Particle {
+                    velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                    timer: Timer::from_seconds(rng.gen_range(0.3..0.6), false),
+                })
+                .insert(RoomMember);
+        }
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    time: Res<GameTime>,
+) {
+    for (ent, mut particle, mut transform, mut sprite) in &mut particles {
+        particle.timer.tick(time.delta());
+        transform.translation += particle.velocity.extend(0.0) * time.delta_seconds();
+        //Slow down as they fly out
+        particle.velocity *= 1.0 - (4.0 * time.delta_seconds()).min(1.0);
+        sprite.color.set_a(particle.timer.percent_left());
+        if particle.timer.just_finished() {
+            commands.entity(ent).despawn_recursive();
+        }
+    }
+}
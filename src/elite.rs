@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use bevy::ecs::system::EntityCommands;
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+pub struct ElitePlugin;
+
+//Chance a room enemy rolls as an elite goes up this much per room away from the start
+const ELITE_CHANCE_PER_ROOM: f32 = 0.04;
+const MAX_ELITE_CHANCE: f32 = 0.35;
+//Elites are a bit bigger so they stand out even before the tint registers
+const ELITE_SCALE: f32 = 1.2;
+
+/// Rare upgrades an enemy def can opt into with its `elites` list
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EliteModifier {
+    Armored,
+    Fast,
+    //Breaks into two smaller copies when it dies
+    Splitting,
+    //Contact hits poison the player
+    Poisonous,
+    Regenerating,
+}
+
+/// Marks an enemy as an elite and which modifiers it rolled
+#[derive(Component, Clone)]
+pub struct Elite(pub Vec<EliteModifier>);
+
+impl Elite {
+    pub fn has(&self, modifier: EliteModifier) -> bool {
+        self.0.contains(&modifier)
+    }
+}
+
+#[derive(Component)]
+pub struct SplitOnDeath {
+    pub def_name: String,
+}
+
+#[derive(Component)]
+pub struct Regenerating {
+    pub per_second: f32,
+}
+
+impl EliteModifier {
+    pub fn modify_stats(&self, stats: &mut Enemy, health: &mut f32) {
+        match self {
+            EliteModifier::Armored => *health *= 1.5,
+            EliteModifier::Fast => {
+                stats.speed *= 1.5;
+                stats.attack_speed *= 1.3;
+                stats.charge_time *= 0.6;
+                stats.cooldown_time *= 0.7;
+            }
+            EliteModifier::Regenerating => *health *= 1.2,
+            EliteModifier::Splitting | EliteModifier::Poisonous => {}
+        }
+    }
+
+    pub fn insert(&self, enemy: &mut EntityCommands, def: &EnemyDef, health: f32) {
+        match self {
+            EliteModifier::Armored => {
+                enemy.insert(Resistances(HashMap::from([
+                    (DamageKind::Slash, 0.5),
+                    (DamageKind::Projectile, 0.5),
+                ])));
+            }
+            EliteModifier::Splitting => {
+                enemy.insert(SplitOnDeath {
+                    def_name: def.name.clone(),
+                });
+            }
+            EliteModifier::Regenerating => {
+                enemy.insert(Regenerating {
+                    per_second: health * 0.05,
+                });
+            }
+            //Fast only changes stats, Poisonous is checked with Elite::has in enemy_collision
+            EliteModifier::Fast | EliteModifier::Poisonous => {}
+        }
+    }
+
+    pub fn tint(&self) -> Color {
+        match self {
+            EliteModifier::Armored => Color::rgb(0.6, 0.7, 1.0),
+            EliteModifier::Fast => Color::rgb(1.0, 1.0, 0.4),
+            EliteModifier::Splitting => Color::rgb(1.0, 0.6, 0.9),
+            EliteModifier::Poisonous => Color::rgb(0.5, 1.0, 0.4),
+            EliteModifier::Regenerating => Color::rgb(1.0, 0.5, 0.5),
+        }
+    }
+}
+
+/// Multiplies the def's own tint by each modifier's tint
+pub fn elite_color(base: Color, modifiers: &[EliteModifier]) -> Color {
+    modifiers.iter().fold(base, |color, modifier| {
+        let tint = modifier.tint();
+        Color::rgb(
+            color.r() * tint.r(),
+            color.g() * tint.g(),
+            color.b() * tint.b(),
+        )
+    })
+}
+
+pub fn elite_scale(modifiers: &[EliteModifier]) -> f32 {
+    if modifiers.is_empty() {
+        1.0
+    } else {
+        ELITE_SCALE
+    }
+}
+
+/// Picks elite modifiers for a normal room spawn, usually none
+pub fn roll_elite(def: &EnemyDef, map: &MapDesc, rng: &mut impl Rng) -> Vec<EliteModifier> {
+    let chance = (map.distance_from_start() as f32 * ELITE_CHANCE_PER_ROOM).min(MAX_ELITE_CHANCE);
+    if def.elites.is_empty() || !rng.gen_bool(chance as f64) {
+        return Vec::default();
+    }
+    def.elites.choose(rng).copied().into_iter().collect()
+}
+
+impl Plugin for ElitePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(regenerate_elites)
+            .add_system_set(SystemSet::on_update(GameState::Main).with_system(split_on_death));
+    }
+}
+
+fn regenerate_elites(mut enemies: Query<(&Regenerating, &mut Health)>, time: Res<GameTime>) {
+    for (regen, mut health) in &mut enemies {
+        //Dead is dead, and no healing while still reeling from a hit
+        if health.health <= 0.0 || health.flashing {
+            continue;
+        }
+        let amount = regen.per_second * time.delta_seconds();
+        health.heal(amount);
+    }
+}
+
+fn split_on_death(
+    mut commands: Commands,
+    mut deaths: EventReader<DeathEvent>,
+    splitters: Query<(&SplitOnDeath, &Transform)>,
+    game_assets: Res<GameAssets>,
+    enemy_defs: Res<Assets<EnemyDef>>,
+) {
+    for death in deaths.iter() {
+        let (split, transform) = match splitters.get(death.0) {
+            Ok(splitter) => splitter,
+            Err(_) => continue,
+        };
+        if let Some(def) = find_enemy_def(&game_assets, &enemy_defs, &split.def_name) {
+            //Halves are weaker and don't drop anything so splitting isn't a loot farm
+            let mut half = def.clone();
+            half.health *= 0.4;
+            half.scale *= 0.7;
+            half.drops = DropTable::default();
+            for offset in [-60.0, 60.0] {
+                let pos = transform.translation + Vec3::new(offset, 0.0, 0.0);
+                spawn_enemy(&mut commands, &half, pos, &[]);
+            }
+        }
+    }
+}
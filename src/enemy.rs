@@ -1,41 +1,49 @@
-use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
 
 use crate::prelude::*;
 
 pub struct EnemyPlugin;
 
-#[derive(Component, Reflect, Default)]
+#[derive(Component, Reflect, Default, Clone, Debug, Deserialize)]
 #[reflect(Component)]
 pub struct Enemy {
-    speed: f32,
-    attack_speed: f32,
-    target_offset: f32,
-    charge_time: f32,
-    attack_time: f32,
-    wait_time: f32,
-    jump_time: f32,
-    cooldown_time: f32,
+    pub speed: f32,
+    pub attack_speed: f32,
+    pub target_offset: f32,
+    pub charge_time: f32,
+    pub attack_time: f32,
+    pub wait_time: f32,
+    pub jump_time: f32,
+    pub cooldown_time: f32,
 }
 
-#[derive(Component)]
-pub enum EnemyType {
-    Frog,
-    Bat,
-    Turtle,
+/// Color the sprite goes back to after flashing red while attacking
+#[derive(Component, Clone, Copy)]
+pub struct BaseColor(pub Color);
+
+impl BaseColor {
+    //Fades green and blue out so the enemy glows red, keeps whatever alpha the flash left
+    fn tinted(&self, amount: f32, alpha: f32) -> Color {
+        Color::rgba(self.0.r(), self.0.g() * amount, self.0.b() * amount, alpha)
+    }
 }
 
 //TODO should state transistions be impled on this or just let systems set it willy nilly
 //TODO see if willy nilly is actually how that is spelled
 //The flow here is the ai moves toward the player, once in range it starts winding up to hit
 //Then the hit, then a cooldown, then it goes back to move toward the player
+//How it gets in range and how it moves during the attack is up to its behaviours in ai/
 #[derive(Component, Clone, Default, Reflect)]
 #[reflect(Component)]
 pub enum AiStage {
-    //Originally MoveToward but that was misleading because the AI always moves toward the player
+    //Hasn't noticed the player yet, see Aggro
     #[default]
+    Idle,
+    //Originally MoveToward but that was misleading because the AI always moves toward the player
     GetInRange,
-    Wait(Timer),
-    Jumping(Timer),
+    //Backing off instead of getting in range, see FleeWhenHurt
+    Flee,
     Charge(Timer),
     Attack(Timer),
     CoolDown(Timer),
@@ -45,9 +53,10 @@ pub enum AiStage {
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Enemy>()
+            .add_asset::<EnemyDef>()
+            .init_asset_loader::<EnemyDefLoader>()
             //Why doesn't this show up in the inspector ugh
             .register_type::<AiStage>()
-            .add_system(enemy_movement)
             .add_system(enemy_attack)
             .add_system(enemy_hitbox_disable)
             // on update because it depends on the game assets being loaded
@@ -57,29 +66,45 @@ impl Plugin for EnemyPlugin {
 }
 fn enemies_die(
     mut commands: Commands,
-    mut enemy: Query<
-        (
-            Entity,
-            &Health,
-            Option<&Ingredient>,
-            &GlobalTransform,
-            &mut AiStage,
-        ),
-        With<Enemy>,
-    >,
-    time: Res<Time>,
+    mut enemy: Query<(Entity, Option<&Drops>, &GlobalTransform, &mut AiStage), With<Enemy>>,
+    mut deaths: EventReader<DeathEvent>,
+    time: Res<GameTime>,
     assets: Res<GameAssets>,
+    mut particles: EventWriter<ParticleEvent>,
 ) {
-    for (ent, health, drop, transform, mut ai_stage) in &mut enemy {
-        if health.health <= 0.0 && !matches!(*ai_stage, AiStage::Dieing(..)) {
+    for death in deaths.iter() {
+        if let Ok((_, _, _, mut ai_stage)) = enemy.get_mut(death.0) {
             *ai_stage = AiStage::Dieing(Timer::from_seconds(1.0, false));
         }
+    }
+    for (ent, drops, transform, mut ai_stage) in &mut enemy {
         if let AiStage::Dieing(mut timer) = ai_stage.clone() {
             timer.tick(time.delta());
             if timer.just_finished() {
                 commands.entity(ent).despawn_recursive();
-                if let Some(drop) = drop {
-                    spawn_drop(&mut commands, *drop, transform.translation(), &assets);
+                particles.send(ParticleEvent {
+                    position: transform.translation(),
+                    color: Color::rgb(0.4, 0.3, 0.5),
+                    count: 24,
+                    speed: 450.0,
+                });
+                if let Some(drops) = drops {
+                    //Spread multiple drops out a bit so they don't stack on one spot
+                    let spread = if drops.0.len() > 1 { 40.0 } else { 0.0 };
+                    let mut rng = rand::thread_rng();
+                    for ingredient in &drops.0 {
+                        let offset = Vec3::new(
+                            rng.gen_range(-spread..=spread),
+                            rng.gen_range(-spread..=spread),
+                            0.0,
+                        );
+                        spawn_drop(
+                            &mut commands,
+                            *ingredient,
+                            transform.translation() + offset,
+                            &assets,
+                        );
+                    }
                 }
             }
             //ugh
@@ -88,258 +113,110 @@ fn enemies_die(
     }
 }
 
-pub fn spawn_boss(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec3) {
+/// Spawns a creature from its def, `elite` is usually empty, see `roll_elite`
+pub fn spawn_enemy(commands: &mut Commands, def: &EnemyDef, pos: Vec3, elite: &[EliteModifier]) {
     let mut pos = pos;
     pos.z = 10.0;
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            sprite: TextureAtlasSprite { ..default() },
-            texture_atlas: assets.turtle.clone(),
-            transform: Transform::from_translation(pos).with_scale(Vec3::splat(2.5)),
-            ..default()
-        })
-        .insert(Enemy {
-            speed: 20.0,
-            attack_speed: 250.0,
-            target_offset: 200.0,
-            charge_time: 0.5,
-            attack_time: 0.8,
-            wait_time: 0.8,
-            jump_time: 0.4,
-            cooldown_time: 0.5,
-        })
-        .insert(Health {
-            health: 140.,
-            flashing: false,
-            damage_flash_timer: Timer::from_seconds(0.6, true),
-            damage_flash_times_per_hit: 5,
-        })
-        .insert(EnemyType::Turtle)
-        .insert(Animation {
-            current_frame: 0,
-            timer: Timer::from_seconds(0.35, true),
-        })
-        .insert(CollisionShape::Cuboid {
-            half_extends: Vec3::new(130.0, 35.0, 1.0),
-            border_radius: Some(20.0),
-        })
-        .insert(RotationConstraints::lock())
-        .insert(RigidBody::Dynamic)
-        .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::Enemy))
-        .insert(Damping::from_linear(10.5).with_angular(0.2))
-        .insert(AiStage::GetInRange)
-        .insert(RoomMember)
-        .insert(Name::new("Turtle"));
-}
-pub fn spawn_bat(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec3) {
-    let bat_drops = vec![
-        Ingredient::BatWings,
-        Ingredient::BatEyes,
-        Ingredient::BatEars,
-    ];
-    //Bat
-    let mut pos = pos;
-    pos.z = 10.0;
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            sprite: TextureAtlasSprite { ..default() },
-            texture_atlas: assets.bat.clone(),
-            transform: Transform::from_translation(pos).with_scale(Vec3::splat(2.5)),
-            ..default()
-        })
-        .insert(Enemy {
-            speed: 40.0,
-            attack_speed: 550.0,
-            target_offset: 350.0,
-            charge_time: 0.5,
-            attack_time: 0.8,
-            wait_time: 0.8,
-            jump_time: 0.4,
-            cooldown_time: 0.5,
-        })
+    let mut stats = def.stats.clone();
+    let mut health = def.health;
+    for modifier in elite {
+        modifier.modify_stats(&mut stats, &mut health);
+    }
+    let color = elite_color(def.color(), elite);
+    let scale = def.scale * elite_scale(elite);
+    let mut enemy = commands.spawn_bundle(SpriteSheetBundle {
+        sprite: TextureAtlasSprite { color, ..default() },
+        texture_atlas: def.atlas.clone(),
+        transform: Transform::from_translation(pos).with_scale(Vec3::splat(scale)),
+        ..default()
+    });
+    enemy
+        .insert(BaseColor(color))
         .insert(Health {
-            health: 20.,
+            health,
+            max_health: health,
             flashing: false,
             damage_flash_timer: Timer::from_seconds(0.6, true),
             damage_flash_times_per_hit: 5,
+            iframe_time: 0.0,
+            invulnerable: 0.0,
         })
-        .insert(EnemyType::Bat)
         .insert(Animation {
             current_frame: 0,
-            timer: Timer::from_seconds(0.35, true),
+            timer: Timer::from_seconds(def.animation.frame_time, true),
         })
-        .insert(*bat_drops.choose(&mut rand::thread_rng()).unwrap())
-        .insert(CollisionShape::Sphere { radius: 50.0 })
+        .insert(def.animation.clone())
+        .insert(def.collider.to_shape())
         .insert(RotationConstraints::lock())
         .insert(RigidBody::Dynamic)
+        .insert(Velocity::default())
         .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::Enemy))
         .insert(Damping::from_linear(10.5).with_angular(0.2))
-        .insert(AiStage::GetInRange)
-        .insert(RoomMember)
-        .insert(Name::new("Bat"));
-}
-
-pub fn spawn_frog(commands: &mut Commands, assets: &Res<GameAssets>, pos: Vec3) {
-    let frog_drops = vec![
-        Ingredient::FrogEyes,
-        Ingredient::FrogLungs,
-        Ingredient::FrogLegs,
-    ];
-    let mut pos = pos;
-    pos.z = 10.0;
-    //Frog
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            sprite: TextureAtlasSprite { ..default() },
-            texture_atlas: assets.frog.clone(),
-            transform: Transform::from_translation(pos).with_scale(Vec3::splat(2.5)),
-            ..default()
-        })
-        .insert(Enemy {
-            speed: 140.0,
-            attack_speed: 450.0,
-            target_offset: 150.0,
-            charge_time: 1.0,
-            attack_time: 0.4,
-            wait_time: 0.8,
-            jump_time: 0.4,
-            cooldown_time: 0.5,
-        })
-        .insert(Health {
-            health: 30.,
-            flashing: false,
-            damage_flash_timer: Timer::from_seconds(0.6, true),
-            damage_flash_times_per_hit: 5,
-        })
-        .insert(EnemyType::Frog)
-        .insert(Animation {
-            current_frame: 0,
-            timer: Timer::from_seconds(0.35, true),
-        })
-        .insert(*frog_drops.choose(&mut rand::thread_rng()).unwrap())
-        .insert(CollisionShape::Sphere { radius: 50.0 })
-        .insert(RotationConstraints::lock())
+        .insert(AiStage::Idle)
+        .insert(Aggro::new(&def.aggro, pos))
+        .insert(Navigation::default())
         .insert(RoomMember)
-        .insert(RigidBody::Dynamic)
-        .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::Enemy))
-        .insert(Damping::from_linear(10.5).with_angular(0.2))
-        .insert(AiStage::Wait(Timer::from_seconds(0.8, false)))
-        .insert(Name::new("Frog"));
+        .insert(Name::new(def.name.clone()));
+    for behaviour in &def.behaviours {
+        behaviour.insert(&mut enemy, &stats);
+    }
+    //Bosses get the big bar on screen instead
+    if matches!(def.spawn, SpawnRule::Boss) {
+        enemy.insert(Boss);
+    } else {
+        spawn_health_bar(&mut enemy, scale);
+    }
+    enemy.insert(stats);
+    let mut drops = def.drops.roll();
+    if !elite.is_empty() {
+        for modifier in elite {
+            modifier.insert(&mut enemy, def, health);
+        }
+        enemy.insert(Elite(elite.to_vec()));
+        //Elites are worth the trouble
+        drops.extend(def.drops.rarest());
+    }
+    enemy.insert(Drops(drops));
 }
 
-fn enemy_movement(
-    mut enemy: Query<(&Enemy, &mut AiStage, &mut Transform), Without<Player>>,
-    player: Query<&mut Transform, With<Player>>,
-    time: Res<Time>,
+fn enemy_attack(
+    mut enemy: Query<(&Enemy, &BaseColor, &mut AiStage, &mut TextureAtlasSprite)>,
+    time: Res<GameTime>,
 ) {
-    const TOLERANCE: f32 = 1.0;
-    if let Ok(player) = player.get_single() {
-        for (enemy, mut stage, mut transform) in &mut enemy {
-            //normal movement
-            if matches!(*stage, AiStage::GetInRange) {
-                let player_dir = (player.translation - transform.translation).normalize();
-                let target = player.translation - player_dir * enemy.target_offset;
-                let direction = target - transform.translation;
-                if direction.length_squared() > TOLERANCE {
-                    transform.translation +=
-                        direction.normalize() * enemy.speed * time.delta_seconds();
+    for (enemy, base, mut stage, mut sprite) in &mut enemy {
+        //clone here to make rust happy, idk why
+        match stage.clone() {
+            AiStage::Idle | AiStage::GetInRange | AiStage::Flee | AiStage::Dieing(..) => continue,
+            AiStage::Charge(mut timer) => {
+                sprite.color = base.tinted(timer.percent_left(), sprite.color.a());
+
+                timer.tick(time.delta());
+                if timer.just_finished() {
+                    *stage = AiStage::Attack(Timer::from_seconds(enemy.attack_time, false));
                 } else {
-                    *stage = AiStage::Charge(Timer::from_seconds(enemy.charge_time, false));
+                    //Why do I need to reset this, rust pls
+                    *stage = AiStage::Charge(timer);
                 }
             }
-            //Frog movement
-            if let AiStage::Wait(mut timer) = stage.clone() {
+            AiStage::Attack(mut timer) => {
+                sprite.color = base.tinted(0.0, sprite.color.a());
+
                 timer.tick(time.delta());
                 if timer.just_finished() {
-                    *stage = AiStage::Jumping(Timer::from_seconds(enemy.jump_time, false));
+                    *stage = AiStage::CoolDown(Timer::from_seconds(enemy.cooldown_time, false));
                 } else {
-                    *stage = AiStage::Wait(timer.clone());
+                    //Why do I need to reset this, rust pls
+                    *stage = AiStage::Attack(timer);
                 }
             }
-            if let AiStage::Jumping(mut timer) = stage.clone() {
-                let player_dir = (player.translation - transform.translation).normalize();
-                let target = player.translation - player_dir * enemy.target_offset;
-                let direction = target - transform.translation;
-                if direction.length_squared() > TOLERANCE {
-                    transform.translation +=
-                        direction.normalize() * enemy.speed * time.delta_seconds();
-                    timer.tick(time.delta());
-                    if timer.just_finished() {
-                        *stage = AiStage::Wait(Timer::from_seconds(enemy.wait_time, false));
-                    } else {
-                        *stage = AiStage::Jumping(timer.clone());
-                    }
+            AiStage::CoolDown(mut timer) => {
+                sprite.color = base.tinted(timer.percent(), sprite.color.a());
+                timer.tick(time.delta());
+                if timer.just_finished() {
+                    *stage = AiStage::GetInRange;
                 } else {
-                    *stage = AiStage::Charge(Timer::from_seconds(enemy.charge_time, false));
-                }
-            }
-        }
-    }
-}
-
-fn enemy_attack(
-    mut enemy: Query<
-        (
-            &Enemy,
-            &EnemyType,
-            &mut AiStage,
-            &mut Transform,
-            &mut TextureAtlasSprite,
-        ),
-        Without<Player>,
-    >,
-    player: Query<&mut Transform, With<Player>>,
-    time: Res<Time>,
-) {
-    if let Ok(player) = player.get_single() {
-        for (enemy, enemy_type, mut stage, mut transform, mut sprite) in &mut enemy {
-            //clone here to make rust happy, idk why
-            match stage.clone() {
-                AiStage::GetInRange
-                | AiStage::Dieing(..)
-                | AiStage::Jumping(..)
-                | AiStage::Wait(..) => continue,
-                AiStage::Charge(mut timer) => {
-                    sprite.color = Color::rgb(1.0, timer.percent_left(), timer.percent_left());
-
-                    timer.tick(time.delta());
-                    if timer.just_finished() {
-                        *stage = AiStage::Attack(Timer::from_seconds(enemy.attack_time, false));
-                    } else {
-                        //Why do I need to reset this, rust pls
-                        *stage = AiStage::Charge(timer);
-                    }
-                }
-                AiStage::Attack(mut timer) => {
-                    sprite.color = Color::rgb(1.0, 0.0, 0.0);
-
-                    timer.tick(time.delta());
-                    if timer.just_finished() {
-                        *stage = AiStage::CoolDown(Timer::from_seconds(enemy.cooldown_time, false));
-                    } else {
-                        //Why do I need to reset this, rust pls
-                        *stage = AiStage::Attack(timer);
-                    }
-                    let player_dir = (player.translation - transform.translation
-                        + Vec3::new(3.0, 0.0, 0.0))
-                    .normalize();
-                    transform.translation +=
-                        player_dir.normalize() * enemy.attack_speed * time.delta_seconds();
-                    transform.translation.z = 10.0;
-                }
-                AiStage::CoolDown(mut timer) => {
-                    sprite.color = Color::rgb(1.0, timer.percent(), timer.percent());
-                    timer.tick(time.delta());
-                    if timer.just_finished() {
-                        if matches!(enemy_type, EnemyType::Frog) {
-                            *stage = AiStage::Wait(Timer::from_seconds(enemy.wait_time, false));
-                        } else {
-                            *stage = AiStage::GetInRange;
-                        }
-                    } else {
-                        //Why do I need to reset this, rust pls
-                        *stage = AiStage::CoolDown(timer);
-                    }
+                    //Why do I need to reset this, rust pls
+                    *stage = AiStage::CoolDown(timer);
                 }
             }
         }
@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+
+use bevy::{
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::prelude::*;
+
+/// Everything needed to spawn a creature, loaded from `assets/Enemies/*.enemy.ron` so new
+/// enemies don't need any code
+#[derive(Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "5d1cb0b6-3a4e-4f2e-9a8c-3c7f4d8b1e21"]
+pub struct EnemyDef {
+    pub name: String,
+    pub stats: Enemy,
+    pub health: f32,
+    pub behaviours: Vec<AiBehaviour>,
+    #[serde(default)]
+    pub aggro: AggroDef,
+    pub collider: ColliderDef,
+    pub scale: f32,
+    //Tint for the whole sprite so variants can share a sheet
+    #[serde(default)]
+    pub color: Option<(f32, f32, f32)>,
+    pub sprite: SpriteDef,
+    pub animation: EnemyAnimations,
+    pub drops: DropTable,
+    pub spawn: SpawnRule,
+    //Modifiers this creature can roll as an elite, empty means it never does
+    #[serde(default)]
+    pub elites: Vec<EliteModifier>,
+    //Built by the loader from `sprite`
+    #[serde(skip)]
+    pub atlas: Handle<TextureAtlas>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum ColliderDef {
+    Sphere {
+        radius: f32,
+    },
+    Cuboid {
+        half_extends: (f32, f32),
+        border_radius: Option<f32>,
+    },
+}
+
+impl ColliderDef {
+    pub fn to_shape(&self) -> CollisionShape {
+        match self {
+            ColliderDef::Sphere { radius } => CollisionShape::Sphere { radius: *radius },
+            ColliderDef::Cuboid {
+                half_extends,
+                border_radius,
+            } => CollisionShape::Cuboid {
+                half_extends: Vec3::new(half_extends.0, half_extends.1, 1.0),
+                border_radius: *border_radius,
+            },
+        }
+    }
+}
+
+/// Same numbers the `texture_atlas` attribute on `GameAssets` takes
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpriteDef {
+    pub path: String,
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub rows: usize,
+    pub padding: (f32, f32),
+}
+
+/// How the room loader should place this enemy
+#[derive(Deserialize, Clone, Debug)]
+pub enum SpawnRule {
+    //Random count between min and max (inclusive) in every normal room
+    Room { min: usize, max: usize },
+    //Only in the boss room
+    Boss,
+}
+
+impl EnemyDef {
+    pub fn color(&self) -> Color {
+        self.color
+            .map_or(Color::WHITE, |(r, g, b)| Color::rgb(r, g, b))
+    }
+}
+
+/// Looks up a loaded enemy definition by its `name`
+pub fn find_enemy_def<'a>(
+    assets: &GameAssets,
+    defs: &'a Assets<EnemyDef>,
+    name: &str,
+) -> Option<&'a EnemyDef> {
+    assets
+        .enemies
+        .iter()
+        .filter_map(|handle| defs.get(handle))
+        .find(|def| def.name == name)
+}
+
+#[derive(Default)]
+pub struct EnemyDefLoader;
+
+impl AssetLoader for EnemyDefLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let mut def: EnemyDef = ron::de::from_bytes(bytes)?;
+
+            let texture_path = AssetPath::new(PathBuf::from(&def.sprite.path), None);
+            let texture: Handle<Image> = load_context.get_handle(texture_path.clone());
+            let atlas = TextureAtlas::from_grid_with_padding(
+                texture,
+                Vec2::new(def.sprite.tile_size.0, def.sprite.tile_size.1),
+                def.sprite.columns,
+                def.sprite.rows,
+                Vec2::new(def.sprite.padding.0, def.sprite.padding.1),
+            );
+            def.atlas = load_context.set_labeled_asset("atlas", LoadedAsset::new(atlas));
+
+            load_context.set_default_asset(LoadedAsset::new(def).with_dependency(texture_path));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["enemy.ron"]
+    }
+}
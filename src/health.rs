@@ -4,13 +4,44 @@ use crate::{inventory::spawn_inventory_ui, prelude::*};
 #[reflect(Component)]
 pub struct Health {
     pub health: f32,
+    //Healing never goes past this, for the player it's how many hearts show
+    pub max_health: f32,
     pub flashing: bool,
     pub damage_flash_timer: Timer,
     pub damage_flash_times_per_hit: usize,
+    //Seconds of invulnerability after taking a hit
+    pub iframe_time: f32,
+    //Seconds of invulnerability left
+    pub invulnerable: f32,
 }
+impl Health {
+    /// Heals without going over max health
+    pub fn heal(&mut self, amount: f32) {
+        self.health = (self.health + amount).min(self.max_health);
+    }
+
+    /// Changes max health, health goes up with it but never ends up above it
+    pub fn set_max_health(&mut self, max_health: f32) {
+        let gained = (max_health - self.max_health).max(0.0);
+        self.max_health = max_health;
+        self.heal(gained);
+        self.health = self.health.min(max_health);
+    }
+}
+
+//The clipped full heart inside a container, its width is how full that heart is
 #[derive(Component)]
 pub struct HealthUI(usize);
 
+//One heart container, there's one per point of max health
+#[derive(Component)]
+pub struct HeartContainer(usize);
+
+#[derive(Component)]
+pub struct HeartRow;
+
+const HEART_SIZE: f32 = 1.8 * 32.0;
+
 pub struct HealthPlugin;
 #[derive(Component)]
 pub struct MainUI;
@@ -22,38 +53,128 @@ impl Plugin for HealthPlugin {
                 SystemSet::on_enter(GameState::Main)
                     .with_system(spawn_health_ui.before(spawn_inventory_ui)),
             )
-            .add_system_set(SystemSet::on_update(GameState::Main).with_system(update_health_ui))
+            .add_system_set(
+                SystemSet::on_update(GameState::Main)
+                    .with_system(sync_heart_containers)
+                    .with_system(update_health_ui.after(sync_heart_containers)),
+            )
             .add_system(sword_collision)
             .add_system_to_stage(CoreStage::PostUpdate, damage_flash)
             .add_system(enemy_collision)
             .add_system(player_death);
     }
 }
-fn player_death(player: Query<&Health, With<Player>>, mut state: ResMut<State<GameState>>) {
-    if let Ok(player) = player.get_single() {
-        if player.health <= 0.0 {
+fn player_death(
+    mut deaths: EventReader<DeathEvent>,
+    player: Query<(), With<Player>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for death in deaths.iter() {
+        if player.contains(death.0) {
             let _ = state.set(GameState::Menu);
         }
     }
 }
 
 fn update_health_ui(
-    mut hearts: Query<(&mut UiImage, &HealthUI)>,
+    mut hearts: Query<(&mut Style, &HealthUI)>,
     player: Query<&Health, With<Player>>,
-    assets: Res<GameAssets>,
 ) {
     if let Ok(player) = player.get_single() {
-        for (mut image, heart) in &mut hearts {
-            if player.health < heart.0 as f32 {
-                *image = assets.heart_empty.clone().into();
-            } else {
-                *image = assets.heart.clone().into();
+        for (mut style, heart) in &mut hearts {
+            //Round down to the nearest half heart
+            let fill = ((player.health - (heart.0 - 1) as f32) * 2.0).floor() / 2.0;
+            style.size.width = Val::Percent(fill.clamp(0.0, 1.0) * 100.0);
+        }
+    }
+}
+
+fn sync_heart_containers(
+    mut commands: Commands,
+    row: Query<Entity, With<HeartRow>>,
+    containers: Query<(Entity, &HeartContainer)>,
+    player: Query<&Health, With<Player>>,
+    assets: Res<GameAssets>,
+) {
+    let (row, player) = match (row.get_single(), player.get_single()) {
+        (Ok(row), Ok(player)) => (row, player),
+        _ => return,
+    };
+    let wanted = player.max_health.ceil().max(0.0) as usize;
+    let current = containers.iter().count();
+    if wanted > current {
+        commands.entity(row).with_children(|parent| {
+            for i in current + 1..=wanted {
+                spawn_heart(parent, &assets, i);
+            }
+        });
+    } else if wanted < current {
+        for (ent, container) in &containers {
+            if container.0 > wanted {
+                commands.entity(ent).despawn_recursive();
             }
         }
     }
 }
 
-fn spawn_health_ui(mut commands: Commands, assets: Res<GameAssets>) {
+fn spawn_heart(parent: &mut ChildBuilder, assets: &GameAssets, i: usize) {
+    parent
+        .spawn_bundle(ImageBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexEnd,
+                align_self: AlignSelf::FlexEnd,
+                size: Size::new(Val::Px(HEART_SIZE), Val::Px(HEART_SIZE)),
+                margin: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            image: assets.heart_empty.clone().into(),
+            ..default()
+        })
+        .insert(ScaledSize(Size::new(
+            Val::Px(HEART_SIZE),
+            Val::Px(HEART_SIZE),
+        )))
+        .insert(HeartContainer(i))
+        .with_children(|parent| {
+            //Clips the full heart so only part of it covers the empty one
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Px(0.0),
+                            bottom: Val::Px(0.0),
+                            ..default()
+                        },
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        overflow: Overflow::Hidden,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .insert(HealthUI(i))
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(ImageBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(HEART_SIZE), Val::Px(HEART_SIZE)),
+                                flex_shrink: 0.0,
+                                ..default()
+                            },
+                            image: assets.heart.clone().into(),
+                            ..default()
+                        })
+                        .insert(ScaledSize(Size::new(
+                            Val::Px(HEART_SIZE),
+                            Val::Px(HEART_SIZE),
+                        )));
+                });
+        });
+}
+
+fn spawn_health_ui(mut commands: Commands) {
     commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -83,29 +204,13 @@ fn spawn_health_ui(mut commands: Commands, assets: Res<GameAssets>) {
                     //color: Color::rgb(0.95, 0.15, 0.15).into(),
                     ..default()
                 })
-                .with_children(|parent| {
-                    for i in 1..6 {
-                        parent
-                            .spawn_bundle(ImageBundle {
-                                style: Style {
-                                    flex_direction: FlexDirection::Row,
-                                    align_items: AlignItems::FlexEnd,
-                                    align_self: AlignSelf::FlexEnd,
-                                    size: Size::new(Val::Px(1.8 * 32.0), Val::Px(1.8 * 32.0)),
-                                    margin: UiRect::all(Val::Px(5.0)),
-                                    ..default()
-                                },
-                                image: assets.heart.clone().into(),
-                                ..default()
-                            })
-                            .insert(HealthUI(i));
-                    }
-                });
+                .insert(HeartRow);
         });
 }
 
-fn damage_flash(mut health: Query<(&mut Health, &mut TextureAtlasSprite)>, time: Res<Time>) {
+fn damage_flash(mut health: Query<(&mut Health, &mut TextureAtlasSprite)>, time: Res<GameTime>) {
     for (mut health, mut sprite) in &mut health {
+        health.invulnerable = (health.invulnerable - time.delta_seconds()).max(0.0);
         if health.flashing {
             health.damage_flash_timer.tick(time.delta());
             let flash = (health.damage_flash_timer.percent()
@@ -126,61 +231,56 @@ fn damage_flash(mut health: Query<(&mut Health, &mut TextureAtlasSprite)>, time:
     }
 }
 
-//Ugh is there a better way
+//Checked every frame against everything the sword overlaps instead of on collision start so
+//enemies already inside the arc when the swing begins still get hit
 fn sword_collision(
-    mut collision_events: EventReader<CollisionEvent>,
-    //Gross
-    sword: Query<&Sword>,
-    mut enemies: Query<&mut Health, With<Enemy>>,
+    mut sword: Query<(Entity, &mut Sword, &Collisions)>,
+    enemies: Query<(), With<Enemy>>,
+    mut damage: EventWriter<DamageEvent>,
 ) {
-    for event in collision_events.iter() {
-        if let CollisionEvent::Started(d1, d2) = event {
-            if sword.contains(d1.rigid_body_entity()) {
-                //ugh
-                if let Ok(mut health) = enemies.get_mut(d2.rigid_body_entity()) {
-                    let sword = sword.get(d1.rigid_body_entity()).unwrap();
-                    if sword.active && !health.flashing {
-                        health.flashing = true;
-                        health.health -= sword.damage;
-                    }
-                }
-            }
-            if sword.contains(d2.rigid_body_entity()) {
-                if let Ok(mut health) = enemies.get_mut(d1.rigid_body_entity()) {
-                    let sword = sword.get(d2.rigid_body_entity()).unwrap();
-                    if sword.active && !health.flashing {
-                        health.flashing = true;
-                        health.health -= sword.damage;
-                    }
-                }
+    for (sword_ent, mut sword, collisions) in &mut sword {
+        if !sword.active {
+            continue;
+        }
+        for enemy_ent in collisions.entities() {
+            if enemies.contains(enemy_ent) && sword.hit.insert(enemy_ent) {
+                damage.send(DamageEvent {
+                    source: sword_ent,
+                    target: enemy_ent,
+                    amount: sword.damage,
+                    kind: DamageKind::Slash,
+                    knockback: sword.knockback,
+                });
             }
         }
     }
 }
 
-//Ugh is there a better way
 fn enemy_collision(
     mut collision_events: EventReader<CollisionEvent>,
-    enemies: Query<&AiStage, With<Enemy>>,
-    mut players: Query<&mut Health, With<Player>>,
+    enemies: Query<(&AiStage, Option<&Elite>), With<Enemy>>,
+    players: Query<(), With<Player>>,
+    mut damage: EventWriter<DamageEvent>,
 ) {
     for event in collision_events.iter() {
         if let CollisionEvent::Started(d1, d2) = event {
-            if let Ok(stage) = enemies.get(d1.rigid_body_entity()) {
-                if let Ok(mut health) = players.get_mut(d2.rigid_body_entity()) {
-                    if !health.flashing && matches!(stage, AiStage::CoolDown(..)) {
-                        health.flashing = true;
-                        health.health -= 1.;
-                    }
-                }
-            }
-            //Ahh there needs to be a better way to try these pairs
-            //Probably something functional but I can't think of it atm
-            if let Ok(stage) = enemies.get(d2.rigid_body_entity()) {
-                if let Ok(mut health) = players.get_mut(d1.rigid_body_entity()) {
-                    if !health.flashing && matches!(stage, AiStage::CoolDown(..)) {
-                        health.flashing = true;
-                        health.health -= 1.;
+            for (enemy_ent, player_ent) in [
+                (d1.rigid_body_entity(), d2.rigid_body_entity()),
+                (d2.rigid_body_entity(), d1.rigid_body_entity()),
+            ] {
+                if let Ok((stage, elite)) = enemies.get(enemy_ent) {
+                    if players.contains(player_ent) && matches!(stage, AiStage::CoolDown(..)) {
+                        damage.send(DamageEvent {
+                            source: enemy_ent,
+                            target: player_ent,
+                            amount: 1.,
+                            kind: if elite.map_or(false, |e| e.has(EliteModifier::Poisonous)) {
+                                DamageKind::Poison
+                            } else {
+                                DamageKind::Contact
+                            },
+                            knockback: 1500.0,
+                        });
                     }
                 }
             }
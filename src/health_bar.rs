@@ -0,0 +1,174 @@
+use bevy::ecs::system::EntityCommands;
+
+use crate::prelude::*;
+
+pub struct HealthBarPlugin;
+
+//World units, before undoing the enemy's own scale
+const BAR_SIZE: Vec2 = Vec2::new(60.0, 7.0);
+const BAR_HEIGHT: f32 = 55.0;
+//Thickness of the dark outline around the fill
+const BAR_BORDER: f32 = 2.0;
+
+/// Marks the one enemy whose health gets the big bar at the top of the screen
+#[derive(Component)]
+pub struct Boss;
+
+/// Root of the little bar floating over an enemy, hidden while the enemy is at full health
+#[derive(Component)]
+pub struct HealthBar;
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
+#[derive(Component)]
+pub struct BossBar;
+
+#[derive(Component)]
+pub struct BossBarFill;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(update_health_bars).add_system_set(
+            SystemSet::on_update(GameState::Main)
+                .with_system(spawn_boss_bar)
+                .with_system(update_boss_bar),
+        );
+    }
+}
+
+/// Adds a health bar as a child of an enemy, `scale` is the enemy's scale so the bar can undo it
+pub fn spawn_health_bar(enemy: &mut EntityCommands, scale: f32) {
+    enemy.with_children(|parent| {
+        parent
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.1, 0.1, 0.1, 0.8),
+                    custom_size: Some(BAR_SIZE + Vec2::splat(BAR_BORDER * 2.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, BAR_HEIGHT / scale, 1.0)
+                    .with_scale(Vec3::splat(1.0 / scale)),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(HealthBar)
+            .insert(Name::new("HealthBar"))
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgb(0.8, 0.1, 0.1),
+                            custom_size: Some(BAR_SIZE),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                        ..default()
+                    })
+                    .insert(HealthBarFill);
+            });
+    });
+}
+
+fn update_health_bars(
+    mut bars: Query<(&Parent, &Children, &mut Visibility), With<HealthBar>>,
+    mut fills: Query<(&mut Transform, &mut Sprite), With<HealthBarFill>>,
+    health: Query<&Health>,
+) {
+    for (parent, children, mut visibility) in &mut bars {
+        let health = match health.get(parent.get()) {
+            Ok(health) => health,
+            Err(_) => continue,
+        };
+        let percent = (health.health / health.max_health).clamp(0.0, 1.0);
+        visibility.is_visible = percent > 0.0 && percent < 1.0;
+        for child in children {
+            if let Ok((mut transform, mut sprite)) = fills.get_mut(*child) {
+                //Shrink toward the left edge instead of the middle
+                sprite.custom_size = Some(Vec2::new(BAR_SIZE.x * percent, BAR_SIZE.y));
+                transform.translation.x = -BAR_SIZE.x * (1.0 - percent) / 2.0;
+            }
+        }
+    }
+}
+
+fn spawn_boss_bar(
+    mut commands: Commands,
+    boss: Query<&Name, With<Boss>>,
+    bars: Query<Entity, With<BossBar>>,
+    assets: Res<GameAssets>,
+) {
+    match (boss.get_single(), bars.get_single()) {
+        (Ok(name), Err(_)) => {
+            commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            left: Val::Percent(20.0),
+                            top: Val::Px(20.0),
+                            ..default()
+                        },
+                        size: Size::new(Val::Percent(60.0), Val::Px(60.0)),
+                        flex_direction: FlexDirection::ColumnReverse,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .insert(BossBar)
+                .insert(MainUI)
+                .insert(Name::new("BossBar"))
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle::from_section(
+                            name.as_str(),
+                            TextStyle {
+                                font: assets.font.clone(),
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                            },
+                        ))
+                        .insert(ScaledFont(32.0));
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.0), Val::Px(16.0)),
+                                padding: UiRect::all(Val::Px(BAR_BORDER)),
+                                ..default()
+                            },
+                            color: Color::rgba(0.1, 0.1, 0.1, 0.8).into(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent
+                                .spawn_bundle(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                                        ..default()
+                                    },
+                                    color: Color::rgb(0.8, 0.1, 0.1).into(),
+                                    ..default()
+                                })
+                                .insert(BossBarFill);
+                        });
+                });
+        }
+        //Boss is gone, so is its bar
+        (Err(_), Ok(bar)) => commands.entity(bar).despawn_recursive(),
+        _ => {}
+    }
+}
+
+fn update_boss_bar(
+    boss: Query<&Health, With<Boss>>,
+    mut fills: Query<&mut Style, With<BossBarFill>>,
+) {
+    if let Ok(health) = boss.get_single() {
+        let percent = (health.health / health.max_health).clamp(0.0, 1.0);
+        for mut style in &mut fills {
+            style.size.width = Val::Percent(percent * 100.0);
+        }
+    }
+}
@@ -1,9 +1,25 @@
 use bevy_inspector_egui::Inspectable;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use crate::prelude::*;
 
-#[derive(Component, Clone, Copy, EnumIter, Hash, PartialEq, Eq, Reflect, Inspectable, Default)]
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    Debug,
+    EnumIter,
+    Hash,
+    PartialEq,
+    Eq,
+    Reflect,
+    Inspectable,
+    Default,
+    Serialize,
+    Deserialize,
+)]
 pub enum Ingredient {
     #[default]
     FrogEyes,
@@ -12,31 +28,144 @@ pub enum Ingredient {
     BatWings,
     BatEyes,
     BatEars,
+    ToadSlime,
+    ToadWarts,
+    TurtleShell,
+    TurtleHeart,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    Common,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    //How much this adds to the strength of any potion it's brewed into
+    pub fn strength(&self) -> u16 {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Rare => 2,
+            Rarity::Legendary => 4,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Rarity::Common => Color::rgb(0.6, 1.0, 0.6),
+            Rarity::Rare => Color::rgb(0.4, 0.6, 1.0),
+            Rarity::Legendary => Color::GOLD,
+        }
+    }
 }
 
 impl Ingredient {
+    pub fn rarity(&self) -> Rarity {
+        match self {
+            Ingredient::FrogEyes
+            | Ingredient::FrogLegs
+            | Ingredient::BatWings
+            | Ingredient::BatEyes
+            | Ingredient::ToadSlime => Rarity::Common,
+            Ingredient::FrogLungs
+            | Ingredient::BatEars
+            | Ingredient::ToadWarts
+            | Ingredient::TurtleShell => Rarity::Rare,
+            Ingredient::TurtleHeart => Rarity::Legendary,
+        }
+    }
+
+    //TODO real art for the toad and turtle parts, they borrow frog and bat sprites with a tint
     pub fn to_sheet_index(&self) -> usize {
         match self {
             Ingredient::FrogEyes => 0,
-            Ingredient::FrogLungs => 2,
+            Ingredient::FrogLungs | Ingredient::TurtleHeart => 2,
             Ingredient::FrogLegs => 1,
             Ingredient::BatWings => 4,
-            Ingredient::BatEyes => 3,
-            Ingredient::BatEars => 5,
+            Ingredient::BatEyes | Ingredient::ToadWarts => 3,
+            Ingredient::BatEars | Ingredient::TurtleShell => 5,
+            Ingredient::ToadSlime => 0,
         }
     }
     pub fn to_sprite(&self, assets: &GameAssets) -> Handle<Image> {
         match self {
-            Ingredient::FrogEyes => assets.frog_eyes.clone(),
-            Ingredient::FrogLungs => assets.frog_lungs.clone(),
+            Ingredient::FrogEyes | Ingredient::ToadSlime => assets.frog_eyes.clone(),
+            Ingredient::FrogLungs | Ingredient::TurtleHeart => assets.frog_lungs.clone(),
             Ingredient::FrogLegs => assets.frog_legs.clone(),
-            Ingredient::BatEyes => assets.bat_eyes.clone(),
+            Ingredient::BatEyes | Ingredient::ToadWarts => assets.bat_eyes.clone(),
             Ingredient::BatWings => assets.bat_wings.clone(),
-            Ingredient::BatEars => assets.bat_ears.clone(),
+            Ingredient::BatEars | Ingredient::TurtleShell => assets.bat_ears.clone(),
+        }
+    }
+    pub fn tint(&self) -> Color {
+        match self {
+            Ingredient::ToadSlime | Ingredient::ToadWarts => Color::rgb(0.8, 0.6, 1.0),
+            Ingredient::TurtleShell => Color::rgb(0.5, 0.9, 0.5),
+            Ingredient::TurtleHeart => Color::GOLD,
+            _ => Color::WHITE,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct WeightedDrop {
+    pub ingredient: Ingredient,
+    pub weight: f32,
+}
+
+fn one_roll() -> usize {
+    1
+}
+
+/// What an enemy leaves behind, each roll picks one entry by weight or nothing at all
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct DropTable {
+    #[serde(default = "one_roll")]
+    pub rolls: usize,
+    //Weight of a roll coming up empty, compared against the entries' weights
+    #[serde(default)]
+    pub nothing: f32,
+    pub entries: Vec<WeightedDrop>,
+}
+
+impl DropTable {
+    pub fn roll(&self) -> Vec<Ingredient> {
+        let mut rng = rand::thread_rng();
+        let total: f32 = self.nothing + self.entries.iter().map(|e| e.weight).sum::<f32>();
+        let mut drops = Vec::default();
+        if total <= 0.0 {
+            return drops;
         }
+        for _ in 0..self.rolls {
+            if rng.gen_range(0.0..total) < self.nothing {
+                continue;
+            }
+            if let Ok(entry) = self.entries.choose_weighted(&mut rng, |entry| entry.weight) {
+                drops.push(entry.ingredient);
+            }
+        }
+        drops
+    }
+
+    /// Highest rarity entry, ties go to whichever is least likely to drop
+    pub fn rarest(&self) -> Option<Ingredient> {
+        self.entries
+            .iter()
+            .max_by(|a, b| {
+                a.ingredient
+                    .rarity()
+                    .cmp(&b.ingredient.rarity())
+                    .then(b.weight.total_cmp(&a.weight))
+            })
+            .map(|entry| entry.ingredient)
     }
 }
 
+/// Whatever an enemy rolled to drop when it dies
+#[derive(Component, Clone, Default)]
+pub struct Drops(pub Vec<Ingredient>);
+
 pub fn spawn_drop(
     commands: &mut Commands,
     to_spawn: Ingredient,
@@ -47,6 +176,7 @@ pub fn spawn_drop(
         .spawn_bundle(SpriteSheetBundle {
             sprite: TextureAtlasSprite {
                 index: to_spawn.to_sheet_index(),
+                color: to_spawn.tint(),
                 ..default()
             },
             texture_atlas: assets.drops.clone(),
@@ -9,8 +9,62 @@ use crate::{potions::Potion, prelude::*};
 #[derive(Component, Default, Inspectable)]
 pub struct Inventory {
     pub items: HashMap<Ingredient, usize>,
+    //Most of each ingredient the player can carry, anything missing can't be carried at all
+    pub caps: HashMap<Ingredient, usize>,
+    //Drops inside this distance drift toward the player
+    pub magnet_radius: f32,
 }
 
+impl Inventory {
+    pub fn new() -> Self {
+        let caps = Ingredient::iter()
+            .map(|ingredient| {
+                let cap = match ingredient.rarity() {
+                    Rarity::Common => 9,
+                    Rarity::Rare => 5,
+                    Rarity::Legendary => 2,
+                };
+                (ingredient, cap)
+            })
+            .collect();
+        Self {
+            items: HashMap::default(),
+            caps,
+            magnet_radius: 200.0,
+        }
+    }
+
+    pub fn count(&self, ingredient: Ingredient) -> usize {
+        *self.items.get(&ingredient).unwrap_or(&0)
+    }
+
+    pub fn is_full(&self, ingredient: Ingredient) -> bool {
+        self.count(ingredient) >= *self.caps.get(&ingredient).unwrap_or(&0)
+    }
+
+    /// Returns false and leaves the inventory alone if it's already full of this ingredient
+    pub fn add(&mut self, ingredient: Ingredient) -> bool {
+        if self.is_full(ingredient) {
+            return false;
+        }
+        *self.items.entry(ingredient).or_insert(0) += 1;
+        true
+    }
+}
+
+/// Drop that has been picked up and is shrinking into the player before it despawns
+#[derive(Component)]
+pub struct PickupAnimation {
+    pub timer: Timer,
+    pub start: Vec3,
+}
+
+const MAGNET_SPEED: f32 = 450.0;
+const SLOT_SIZE: Size<Val> = Size {
+    width: Val::Px(2.5 * 32.0),
+    height: Val::Px(2.5 * 32.0),
+};
+
 pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
@@ -19,6 +73,8 @@ impl Plugin for InventoryPlugin {
             //.register_inspectable::<Inventory>()
             .add_system(update_inventory_ui)
             .add_system(create_potion)
+            .add_system(ingredient_magnet)
+            .add_system(pickup_animation)
             .add_system(player_pickup_ingredient);
     }
 }
@@ -32,9 +88,11 @@ fn create_potion(
         With<Button>,
     >,
     mut inventory: Query<&mut Inventory, With<Player>>,
-    mut player: Query<(&mut Player, &mut Health)>,
+    mut player: Query<(&mut Player, &mut Health, &GlobalTransform)>,
+    mut particles: EventWriter<ParticleEvent>,
+    profile: Res<Profile>,
 ) {
-    if let Ok((mut player, mut health)) = player.get_single_mut() {
+    if let Ok((mut player, mut health, transform)) = player.get_single_mut() {
         if let Ok(mut inventory) = inventory.get_single_mut() {
             let mut first = None;
             let mut clear = false;
@@ -47,8 +105,14 @@ fn create_potion(
                     *item -= 1;
                     let item = inventory.items.get_mut(ingredient).unwrap();
                     *item -= 1;
-                    let potion = Potion::new((*first, *ingredient));
+                    let potion = Potion::new((*first, *ingredient), &profile);
                     potion.consume(&mut player, &mut health);
+                    particles.send(ParticleEvent {
+                        position: transform.translation(),
+                        color: potion.color(),
+                        count: 20,
+                        speed: 250.0,
+                    });
                     clear = true;
                 }
             }
@@ -83,9 +147,14 @@ fn update_inventory_ui(
         for (children, ingredient) in &buttons {
             for child in children {
                 if let Ok(mut text) = text.get_mut(*child) {
-                    let count = inventory.items.get(ingredient).unwrap_or(&0);
-                    *text =
-                        Text::from_section(format!("{}", count), text.sections[0].style.clone());
+                    let count = inventory.count(*ingredient);
+                    let mut style = text.sections[0].style.clone();
+                    style.color = if inventory.is_full(*ingredient) {
+                        Color::MAROON
+                    } else {
+                        Color::BLACK
+                    };
+                    *text = Text::from_section(format!("{}", count), style);
                 }
             }
         }
@@ -127,6 +196,7 @@ pub fn spawn_inventory_ui(
                     //color: Color::rgb(0.95, 0.15, 0.15).into(),
                     ..default()
                 })
+                .insert(ScaledSize(Size::new(Val::Px(200.0), Val::Percent(30.0))))
                 //Item buttons
                 .with_children(|parent| {
                     for ingredient in Ingredient::iter() {
@@ -140,8 +210,10 @@ pub fn spawn_inventory_ui(
                                     ..default()
                                 },
                                 image: ingredient.to_sprite(&assets).into(),
+                                color: ingredient.tint().into(),
                                 ..default()
                             })
+                            .insert(ScaledSize(SLOT_SIZE))
                             .with_children(|parent| {
                                 parent
                                     .spawn_bundle(ButtonBundle {
@@ -157,10 +229,12 @@ pub fn spawn_inventory_ui(
                                         color: Color::NONE.into(),
                                         ..default() //Count text
                                     })
+                                    .insert(ScaledSize(SLOT_SIZE))
                                     .insert(SelectedPotion(false))
                                     .insert(ingredient)
                                     .with_children(|parent| {
-                                        parent.spawn_bundle(TextBundle {
+                                        parent
+                                            .spawn_bundle(TextBundle {
                                             text: Text::from_section(
                                                 "0",
                                                 TextStyle {
@@ -176,7 +250,8 @@ pub fn spawn_inventory_ui(
                                                 ..default()
                                             },
                                             ..default()
-                                        });
+                                        })
+                                            .insert(ScaledFont(20.0));
                                     });
                             });
                     }
@@ -184,36 +259,92 @@ pub fn spawn_inventory_ui(
         });
 }
 
+fn ingredient_magnet(
+    player: Query<(&Transform, &Inventory), With<Player>>,
+    mut drops: Query<(&Ingredient, &mut Transform), (Without<Player>, Without<Enemy>)>,
+    time: Res<GameTime>,
+) {
+    let (player, inventory) = match player.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    for (ingredient, mut transform) in &mut drops {
+        if inventory.is_full(*ingredient) {
+            continue;
+        }
+        let to_player = (player.translation - transform.translation).truncate();
+        let distance = to_player.length();
+        if distance < inventory.magnet_radius && distance > 1.0 {
+            //Speeds up the closer it gets so it snaps in instead of crawling the last bit
+            let pull = 1.0 + (1.0 - distance / inventory.magnet_radius) * 2.0;
+            let step = (MAGNET_SPEED * pull * time.delta_seconds()).min(distance);
+            transform.translation += (to_player / distance * step).extend(0.0);
+        }
+    }
+}
+
+/// Checks everything the player overlaps each frame rather than only new contacts, so a drop that
+/// was touched while full gets picked up as soon as there's room
 fn player_pickup_ingredient(
     mut commands: Commands,
-    mut player: Query<&mut Inventory, With<Player>>,
-    mut drops: Query<(Entity, &Ingredient), Without<Enemy>>,
-    mut collision_events: EventReader<CollisionEvent>,
+    mut player: Query<(&mut Inventory, &Transform, &Collisions), With<Player>>,
+    drops: Query<(&Ingredient, &GlobalTransform), Without<Enemy>>,
+    mut particles: EventWriter<ParticleEvent>,
+    mut sfx: EventWriter<SfxEvent>,
 ) {
-    for event in collision_events.iter() {
-        if let CollisionEvent::Started(d1, d2) = event {
-            if let Ok(mut inventory) = player.get_mut(d2.rigid_body_entity()) {
-                if let Ok((ent, ingredients)) = drops.get_mut(d1.rigid_body_entity()) {
-                    commands.entity(ent).despawn_recursive();
-                    if inventory.items.contains_key(ingredients) {
-                        let count = inventory.items[ingredients] + 1;
-                        inventory.items.insert(*ingredients, count);
-                    } else {
-                        inventory.items.insert(*ingredients, 1);
-                    }
-                }
-            }
-            if let Ok(mut inventory) = player.get_mut(d1.rigid_body_entity()) {
-                if let Ok((ent, ingredients)) = drops.get_mut(d2.rigid_body_entity()) {
-                    commands.entity(ent).despawn_recursive();
-                    if inventory.items.contains_key(ingredients) {
-                        let count = inventory.items[ingredients] + 1;
-                        inventory.items.insert(*ingredients, count);
-                    } else {
-                        inventory.items.insert(*ingredients, 1);
-                    }
-                }
-            }
+    let (mut inventory, player_transform, collisions) = match player.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    for ent in collisions.entities() {
+        let (ingredient, transform) = match drops.get(ent) {
+            Ok(drop) => drop,
+            Err(_) => continue,
+        };
+        if !inventory.add(*ingredient) {
+            //Full, leave it on the ground for later
+            continue;
+        }
+        //No more collisions or pickups, just the animation
+        commands
+            .entity(ent)
+            .remove::<Ingredient>()
+            .remove::<CollisionShape>()
+            .remove::<RigidBody>()
+            .insert(PickupAnimation {
+                timer: Timer::from_seconds(0.25, false),
+                start: transform.translation(),
+            });
+        particles.send(ParticleEvent {
+            position: player_transform.translation,
+            color: ingredient.rarity().color(),
+            count: 10,
+            speed: 200.0,
+        });
+        sfx.send(SfxEvent(Sfx::Pickup));
+    }
+}
+
+fn pickup_animation(
+    mut commands: Commands,
+    mut drops: Query<(Entity, &mut PickupAnimation, &mut Transform), Without<Player>>,
+    player: Query<&Transform, With<Player>>,
+    time: Res<GameTime>,
+) {
+    let player = match player.get_single() {
+        Ok(player) => player.translation,
+        Err(_) => return,
+    };
+    for (ent, mut animation, mut transform) in &mut drops {
+        animation.timer.tick(time.delta());
+        let t = animation.timer.percent();
+        //Hop up a little while sliding into the player and shrinking away
+        let hop = Vec3::Y * 40.0 * (t * std::f32::consts::PI).sin();
+        transform.translation = animation.start.lerp(player, t) + hop;
+        transform.translation.z = animation.start.z;
+        transform.scale = Vec3::splat(2.5 * (1.0 - t));
+        if animation.timer.just_finished() {
+            commands.entity(ent).despawn_recursive();
         }
     }
 }
@@ -1,6 +1,6 @@
 use bevy::{
     render::{render_resource::TextureFormat, texture::ImageSettings},
-    window::PresentMode,
+    window::{PresentMode, WindowResizeConstraints},
 };
 use bevy_asset_loader::prelude::*;
 use bevy_inspector_egui::{WorldInspectorParams, WorldInspectorPlugin};
@@ -9,18 +9,33 @@ use prelude::{health::HealthPlugin, inventory::InventoryPlugin, start_menu::Star
 pub const HEIGHT: f32 = 700.;
 pub const RESOLUTION: f32 = 16.0 / 9.0;
 
+mod ai;
 mod animation;
+mod camera;
+mod combat;
+mod display;
+mod doors;
+mod effects;
+mod elite;
 mod enemy;
+mod enemy_def;
 mod health;
+mod health_bar;
 mod ingredients;
 mod input;
 mod inventory;
 mod map;
 mod mouse;
 mod music;
+mod navigation;
 mod player;
 mod potions;
 mod prelude;
+mod profile;
+mod projectile;
+mod save;
+mod settings;
+mod sfx;
 mod start_menu;
 mod world_gen;
 
@@ -36,36 +51,6 @@ pub struct GameAssets {
     ))]
     #[asset(path = "Witch.png")]
     player: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 67.,
-        tile_size_y = 67.,
-        columns = 4,
-        rows = 2,
-        padding_x = 1.,
-        padding_y = 1.
-    ))]
-    #[asset(path = "Frog.png")]
-    frog: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 144.,
-        tile_size_y = 48.,
-        columns = 6,
-        rows = 1,
-        padding_x = 1.,
-        padding_y = 1.
-    ))]
-    #[asset(path = "Turtle.png")]
-    turtle: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 70.,
-        tile_size_y = 48.,
-        columns = 8,
-        rows = 1,
-        padding_x = 1.,
-        padding_y = 1.
-    ))]
-    #[asset(path = "Bat.png")]
-    bat: Handle<TextureAtlas>,
     #[asset(texture_atlas(
         tile_size_x = 32.,
         tile_size_y = 32.,
@@ -77,6 +62,11 @@ pub struct GameAssets {
     #[asset(path = "Potions.png")]
     drops: Handle<TextureAtlas>,
 
+    #[asset(path = "Enemies", folder(typed))]
+    enemies: Vec<Handle<EnemyDef>>,
+    #[asset(path = "Player/sword.moveset.ron")]
+    sword_moveset: Handle<SwordMoveset>,
+
     #[asset(path = "FrogEyes.png")]
     frog_eyes: Handle<Image>,
     #[asset(path = "FrogLungs.png")]
@@ -94,6 +84,9 @@ pub struct GameAssets {
     heart: Handle<Image>,
     #[asset(path = "Heart_Empty.png")]
     heart_empty: Handle<Image>,
+
+    #[asset(path = "Font/DancingScript-VariableFont_wght.ttf")]
+    font: Handle<Font>,
 }
 
 #[derive(AssetCollection)]
@@ -158,7 +151,12 @@ fn main() {
             height: HEIGHT,
             title: "Potion Game".to_string(),
             present_mode: PresentMode::Fifo,
-            resizable: false,
+            resizable: true,
+            resize_constraints: WindowResizeConstraints {
+                min_width: 640.0,
+                min_height: 360.0,
+                ..default()
+            },
             ..Default::default()
         })
         .add_plugins(DefaultPlugins)
@@ -172,119 +170,28 @@ fn main() {
         .add_plugin(InputPlugin)
         .add_plugin(StartPlugin)
         .add_plugin(PlayerPlugin)
+        .add_plugin(CombatPlugin)
+        .add_plugin(EffectsPlugin)
         .add_plugin(EnemyPlugin)
+        .add_plugin(ElitePlugin)
+        .add_plugin(AiPlugin)
+        .add_plugin(NavigationPlugin)
+        .add_plugin(ProjectilePlugin)
         .add_plugin(HealthPlugin)
+        .add_plugin(HealthBarPlugin)
         .add_plugin(InventoryPlugin)
         .add_plugin(AnimationPlugin)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(DisplayPlugin)
         .add_plugin(MusicPlugin)
+        .add_plugin(SfxPlugin)
         .add_plugin(MapPlugin)
+        .add_plugin(DoorPlugin)
+        .add_plugin(SavePlugin)
+        .add_plugin(ProfilePlugin)
+        .add_plugin(CameraControllerPlugin)
         //One off weird systems
-        .add_startup_system(spawn_camera)
         .insert_resource(MousePos::default())
         .add_system(mouse_position)
-        .add_system_set(SystemSet::on_enter(GameState::Main).with_system(spawn_room_exits))
-        .add_system_set(
-            SystemSet::on_update(GameState::Main)
-                .with_system(camera_follows_player.after(player_movement)),
-        )
         .run();
 }
-
-#[derive(Component, Debug, Clone, Copy)]
-pub enum ExitDirection {
-    North,
-    South,
-    East,
-    West,
-}
-
-fn camera_follows_player(
-    player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<
-        (&mut Transform, &OrthographicProjection),
-        (With<Camera2d>, Without<Player>),
-    >,
-) {
-    let player_transform = player_query.single().translation;
-    let (mut camera_transform, ortho) = camera_query.single_mut();
-
-    camera_transform.translation.x = player_transform.x;
-    camera_transform.translation.y = player_transform.y;
-    let max_cam = 31.0 * 0.8 * 64.0;
-    if camera_transform.translation.y + ortho.top > max_cam {
-        camera_transform.translation.y = max_cam - ortho.top;
-    }
-    if camera_transform.translation.y + ortho.bottom < -max_cam {
-        camera_transform.translation.y = -max_cam - ortho.bottom;
-    }
-    if camera_transform.translation.x + ortho.right > max_cam {
-        camera_transform.translation.x = max_cam - ortho.right;
-    }
-    if camera_transform.translation.x + ortho.left < -max_cam {
-        camera_transform.translation.x = -max_cam - ortho.left;
-    }
-}
-
-fn spawn_camera(mut commands: Commands) {
-    commands.spawn_bundle(Camera2dBundle::default());
-}
-
-fn spawn_room_exits(mut commands: Commands, assets: Res<GameAssets>) {
-    let exit = 31.0 * 0.8 * 64.0;
-    //Left
-    commands
-        .spawn_bundle(SpatialBundle {
-            transform: Transform::from_xyz(-exit, 0.0, 0.0).with_scale(Vec3::new(100., 1100., 1.0)),
-            ..default()
-        })
-        .insert(CollisionShape::Cuboid {
-            half_extends: Vec2::new(50.0, 550.0).extend(1.0),
-            border_radius: None,
-        })
-        .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::World))
-        .insert(RotationConstraints::lock())
-        .insert(ExitDirection::West)
-        .insert(RigidBody::Sensor);
-    //Right
-    commands
-        .spawn_bundle(SpatialBundle {
-            transform: Transform::from_xyz(exit, 0.0, 0.0).with_scale(Vec3::new(100., 1100., 1.0)),
-            ..default()
-        })
-        .insert(CollisionShape::Cuboid {
-            half_extends: Vec2::new(50.0, 550.0).extend(1.0),
-            border_radius: None,
-        })
-        .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::World))
-        .insert(RotationConstraints::lock())
-        .insert(ExitDirection::East)
-        .insert(RigidBody::Sensor);
-    //Top
-    commands
-        .spawn_bundle(SpatialBundle {
-            transform: Transform::from_xyz(0.0, exit, 0.0).with_scale(Vec3::new(1100., 100., 1.0)),
-            ..default()
-        })
-        .insert(CollisionShape::Cuboid {
-            half_extends: Vec2::new(550.0, 50.0).extend(1.0),
-            border_radius: None,
-        })
-        .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::World))
-        .insert(RotationConstraints::lock())
-        .insert(ExitDirection::North)
-        .insert(RigidBody::Sensor);
-    //Bottom
-    commands
-        .spawn_bundle(SpatialBundle {
-            transform: Transform::from_xyz(0.0, -exit, 0.0).with_scale(Vec3::new(1100., 100., 1.0)),
-            ..default()
-        })
-        .insert(CollisionShape::Cuboid {
-            half_extends: Vec2::new(550.0, 50.0).extend(1.0),
-            border_radius: None,
-        })
-        .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::World))
-        .insert(RotationConstraints::lock())
-        .insert(ExitDirection::South)
-        .insert(RigidBody::Sensor);
-}
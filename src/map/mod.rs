@@ -1,8 +1,12 @@
 use bevy::prelude::Plugin;
 use bevy::prelude::*;
 use bevy_procedural::{CellLocation, Direction, SPCell, SuperPositionGrid};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub mod nav;
+pub mod room;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub enum MapTile {
     //Direction of pipe is first exit, second is two directions clockwise
     NPipe,
@@ -0,0 +1,166 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use bevy::prelude::*;
+
+//Integer costs so the open set can be a plain BinaryHeap
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+//Extra cost for hugging walls so paths keep enemies away from corners they'd snag on
+const WALL_HUG_COST: u32 = 30;
+
+/// Walkable grid built from a room's floor and wall tiles.
+/// Cell (0, 0) is the top left tile and y grows downward like the room image
+#[derive(Clone, Debug, Default)]
+pub struct NavGrid {
+    pub width: usize,
+    pub height: usize,
+    pub walkable: Vec<bool>,
+    pub cell_size: f32,
+    //World position of the center of cell (0, 0)
+    pub origin: Vec2,
+}
+
+impl NavGrid {
+    pub fn new(
+        width: usize,
+        height: usize,
+        walkable: Vec<bool>,
+        cell_size: f32,
+        origin: Vec2,
+    ) -> Self {
+        assert_eq!(walkable.len(), width * height);
+        Self {
+            width,
+            height,
+            walkable,
+            cell_size,
+            origin,
+        }
+    }
+
+    pub fn in_bounds(&self, cell: IVec2) -> bool {
+        cell.x >= 0
+            && cell.y >= 0
+            && (cell.x as usize) < self.width
+            && (cell.y as usize) < self.height
+    }
+
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        self.in_bounds(cell) && self.walkable[cell.x as usize + cell.y as usize * self.width]
+    }
+
+    pub fn world_to_cell(&self, pos: Vec2) -> IVec2 {
+        IVec2::new(
+            ((pos.x - self.origin.x) / self.cell_size).round() as i32,
+            ((self.origin.y - pos.y) / self.cell_size).round() as i32,
+        )
+    }
+
+    pub fn cell_to_world(&self, cell: IVec2) -> Vec2 {
+        Vec2::new(
+            self.origin.x + cell.x as f32 * self.cell_size,
+            self.origin.y - cell.y as f32 * self.cell_size,
+        )
+    }
+
+    /// True if nothing but floor lies on the straight line between the two points
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2) -> bool {
+        let distance = from.distance(to);
+        //A few samples per cell is plenty to not skip over a wall
+        let steps = (distance / (self.cell_size / 4.0)).ceil().max(1.0) as usize;
+        (0..=steps).all(|step| {
+            let point = from.lerp(to, step as f32 / steps as f32);
+            self.is_walkable(self.world_to_cell(point))
+        })
+    }
+
+    fn next_to_wall(&self, cell: IVec2) -> bool {
+        neighbors()
+            .iter()
+            .any(|(offset, _)| !self.is_walkable(cell + *offset))
+    }
+
+    /// A* from one world position to another, returns the world positions of each cell along the
+    /// way (including the end) or None if there is no way through
+    pub fn find_path(&self, from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+        let start = self.world_to_cell(from);
+        let goal = self.world_to_cell(to);
+        if !self.is_walkable(start) || !self.is_walkable(goal) {
+            return None;
+        }
+        let index = |cell: IVec2| cell.x as usize + cell.y as usize * self.width;
+        let heuristic = |cell: IVec2| {
+            let d = (cell - goal).abs();
+            let (low, high) = (d.x.min(d.y) as u32, d.x.max(d.y) as u32);
+            DIAGONAL_COST * low + STRAIGHT_COST * (high - low)
+        };
+
+        let mut best = vec![u32::MAX; self.width * self.height];
+        let mut came_from: Vec<Option<IVec2>> = vec![None; self.width * self.height];
+        let mut open = BinaryHeap::new();
+        best[index(start)] = 0;
+        open.push(Reverse((heuristic(start), 0, start.x, start.y)));
+
+        while let Some(Reverse((_, cost, x, y))) = open.pop() {
+            let cell = IVec2::new(x, y);
+            if cell == goal {
+                let mut path = vec![self.cell_to_world(cell)];
+                let mut current = cell;
+                while let Some(previous) = came_from[index(current)] {
+                    if previous == start {
+                        break;
+                    }
+                    path.push(self.cell_to_world(previous));
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if cost > best[index(cell)] {
+                continue;
+            }
+            for (offset, step_cost) in neighbors() {
+                let next = cell + offset;
+                if !self.is_walkable(next) {
+                    continue;
+                }
+                //Don't cut corners diagonally through walls
+                if offset.x != 0
+                    && offset.y != 0
+                    && (!self.is_walkable(IVec2::new(next.x, cell.y))
+                        || !self.is_walkable(IVec2::new(cell.x, next.y)))
+                {
+                    continue;
+                }
+                let mut next_cost = cost + step_cost;
+                if self.next_to_wall(next) {
+                    next_cost += WALL_HUG_COST;
+                }
+                if next_cost < best[index(next)] {
+                    best[index(next)] = next_cost;
+                    came_from[index(next)] = Some(cell);
+                    open.push(Reverse((
+                        next_cost + heuristic(next),
+                        next_cost,
+                        next.x,
+                        next.y,
+                    )));
+                }
+            }
+        }
+        None
+    }
+}
+
+fn neighbors() -> [(IVec2, u32); 8] {
+    [
+        (IVec2::new(1, 0), STRAIGHT_COST),
+        (IVec2::new(-1, 0), STRAIGHT_COST),
+        (IVec2::new(0, 1), STRAIGHT_COST),
+        (IVec2::new(0, -1), STRAIGHT_COST),
+        (IVec2::new(1, 1), DIAGONAL_COST),
+        (IVec2::new(1, -1), DIAGONAL_COST),
+        (IVec2::new(-1, 1), DIAGONAL_COST),
+        (IVec2::new(-1, -1), DIAGONAL_COST),
+    ]
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+//Tileset art is 64 pixels a tile, drawn a bit smaller in the world
+pub const TILE_SCALE: f32 = 0.8;
+//World size of one pixel of a room background
+pub const TILE_SIZE: f32 = 64.0 * TILE_SCALE;
+//Pixels this color in a room background mark its doorways, they're walkable floor
+pub const DOOR_MARKER: (u8, u8, u8) = (255, 0, 255);
+//How many tiles in from a doorway the player appears when coming through it
+const ENTRY_INSET: f32 = 3.0;
+//Rooms drawn without markers get doors this wide in the middle of every wall, wide enough to
+//cover any corridor
+const FALLBACK_DOOR_HALF_WIDTH: f32 = 10.0 * TILE_SIZE;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExitDirection {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl ExitDirection {
+    //Leaving north puts you at the south door of the next room
+    pub fn opposite(&self) -> Self {
+        match self {
+            ExitDirection::North => ExitDirection::South,
+            ExitDirection::South => ExitDirection::North,
+            ExitDirection::East => ExitDirection::West,
+            ExitDirection::West => ExitDirection::East,
+        }
+    }
+
+    /// Points out of the room through this side
+    pub fn normal(&self) -> Vec2 {
+        match self {
+            ExitDirection::North => Vec2::Y,
+            ExitDirection::South => -Vec2::Y,
+            ExitDirection::East => Vec2::X,
+            ExitDirection::West => -Vec2::X,
+        }
+    }
+}
+
+/// Size in tiles of the room that's currently loaded, read from its background image
+#[derive(Clone, Copy, Debug)]
+pub struct RoomSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for RoomSize {
+    fn default() -> Self {
+        Self {
+            width: 64,
+            height: 64,
+        }
+    }
+}
+
+impl RoomSize {
+    pub fn from_image(image: &Image) -> Self {
+        Self {
+            width: image.size().x as usize,
+            height: image.size().y as usize,
+        }
+    }
+
+    /// Center of the top left tile, the room is laid out right and down from here
+    pub fn origin(&self) -> Vec2 {
+        Vec2::new(
+            -((self.width / 2) as f32) * TILE_SIZE,
+            (self.height / 2) as f32 * TILE_SIZE,
+        )
+    }
+
+    /// Tiles in from the middle to an edge, `inset` tiles back from the outer wall
+    pub fn half_extents(&self, inset: usize) -> Vec2 {
+        Vec2::new(
+            (self.width / 2).saturating_sub(inset) as f32,
+            (self.height / 2).saturating_sub(inset) as f32,
+        ) * TILE_SIZE
+    }
+
+    //The outer wall is one tile thick
+    pub fn camera_bounds(&self) -> Vec2 {
+        self.half_extents(1)
+    }
+
+    pub fn exit_distance(&self) -> Vec2 {
+        self.half_extents(1)
+    }
+
+    //Far enough in to not touch the exit sensor that leads straight back
+    pub fn entry_distance(&self) -> Vec2 {
+        self.half_extents(4)
+    }
+
+    pub fn spawn_area(&self) -> Vec2 {
+        self.half_extents(4) * 0.7
+    }
+}
+
+/// A doorway out of the room, found from the door markers in the background image
+#[derive(Clone, Copy, Debug)]
+pub struct Door {
+    pub direction: ExitDirection,
+    //Middle of the doorway
+    pub position: Vec2,
+    //Half the length of the doorway along the wall
+    pub half_width: f32,
+}
+
+impl Door {
+    /// Where the player stands after coming into the room through this door
+    pub fn entry_point(&self) -> Vec2 {
+        self.position - self.direction.normal() * ENTRY_INSET * TILE_SIZE
+    }
+}
+
+pub fn pixel(image: &Image, width: usize, x: usize, y: usize) -> (u8, u8, u8) {
+    let index = 4 * (x + y * width);
+    (
+        image.data[index],
+        image.data[index + 1],
+        image.data[index + 2],
+    )
+}
+
+/// Groups the door marker pixels by the wall they're closest to, one door per wall. Rooms drawn
+/// without markers get a wide door in the middle of every wall
+pub fn find_doors(image: &Image, size: &RoomSize) -> Vec<Door> {
+    let origin = size.origin();
+    let mut markers: HashMap<ExitDirection, Vec<Vec2>> = HashMap::default();
+    for y in 0..size.height {
+        for x in 0..size.width {
+            if pixel(image, size.width, x, y) != DOOR_MARKER {
+                continue;
+            }
+            let edges = [
+                (y, ExitDirection::North),
+                (size.height - 1 - y, ExitDirection::South),
+                (x, ExitDirection::West),
+                (size.width - 1 - x, ExitDirection::East),
+            ];
+            let direction = edges
+                .iter()
+                .min_by_key(|(distance, _)| *distance)
+                .unwrap()
+                .1;
+            markers
+                .entry(direction)
+                .or_default()
+                .push(origin + Vec2::new(x as f32, -(y as f32)) * TILE_SIZE);
+        }
+    }
+
+    if markers.is_empty() {
+        let exit = size.exit_distance();
+        return [
+            ExitDirection::West,
+            ExitDirection::East,
+            ExitDirection::North,
+            ExitDirection::South,
+        ]
+        .into_iter()
+        .map(|direction| Door {
+            direction,
+            position: direction.normal() * exit,
+            half_width: FALLBACK_DOOR_HALF_WIDTH,
+        })
+        .collect();
+    }
+
+    markers
+        .into_iter()
+        .map(|(direction, pixels)| {
+            let position = pixels.iter().copied().sum::<Vec2>() / pixels.len() as f32;
+            //Along the wall is y for the side walls and x for the top and bottom
+            let along = direction.normal().perp().abs();
+            let half_width = pixels
+                .iter()
+                .map(|marker| (*marker - position).dot(along).abs())
+                .fold(0.0, f32::max)
+                + TILE_SIZE / 2.0;
+            Door {
+                direction,
+                position,
+                half_width,
+            }
+        })
+        .collect()
+}
@@ -5,29 +5,180 @@ use bevy_kira_audio::prelude::*;
 
 pub struct MusicPlugin;
 
+/// Kira channel the menu music plays on, kept apart from `SfxChannel`
+pub struct MusicChannel;
+
+//Each in game layer gets its own channel so they can fade against each other
+pub struct ExplorationChannel;
+pub struct CombatChannel;
+pub struct BossChannel;
+
+//Seconds for a layer to fade fully in or out
+const CROSSFADE_SECONDS: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MusicLayer {
+    Exploration,
+    Combat,
+    Boss,
+}
+
+#[derive(Default)]
+pub struct LayerState {
+    pub volume: f32,
+    started: bool,
+    paused: bool,
+}
+
+/// Decides which layer should be playing and fades between them, layers that fade out are paused
+/// instead of stopped so they pick up where they left off next time
+#[derive(Default)]
+pub struct MusicDirector {
+    pub target: Option<MusicLayer>,
+    pub exploration: LayerState,
+    pub combat: LayerState,
+    pub boss: LayerState,
+}
+
 impl Plugin for MusicPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(AudioPlugin)
+            .add_audio_channel::<MusicChannel>()
+            .add_audio_channel::<ExplorationChannel>()
+            .add_audio_channel::<CombatChannel>()
+            .add_audio_channel::<BossChannel>()
+            .insert_resource(MusicDirector::default())
             .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(play_menu_music))
+            .add_system_set(SystemSet::on_enter(GameState::Main).with_system(stop_menu_music))
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu).with_system(apply_menu_music_volume),
+            )
             .add_system_set(
-                SystemSet::on_enter(GameState::Main).with_system(play_background_music),
-            );
+                SystemSet::on_update(GameState::Main)
+                    .with_system(choose_music_layer)
+                    .with_system(crossfade_music.after(choose_music_layer)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Main).with_system(stop_music_layers));
     }
 }
 
-pub fn play_menu_music(assets: Res<AssetServer>, audio: Res<bevy_kira_audio::prelude::Audio>) {
+pub fn play_menu_music(
+    assets: Res<AssetServer>,
+    audio: Res<AudioChannel<MusicChannel>>,
+    settings: Res<AudioSettings>,
+) {
     let tween = AudioTween::new(Duration::from_millis(400), AudioEasing::Linear);
     audio.stop().fade_out(tween);
     audio.play(assets.load("Music/Title.wav")).looped();
+    audio.set_volume(settings.music_volume());
 }
-pub fn play_background_music(
+
+fn apply_menu_music_volume(audio: Res<AudioChannel<MusicChannel>>, settings: Res<AudioSettings>) {
+    if settings.is_changed() {
+        audio.set_volume(settings.music_volume());
+    }
+}
+
+fn stop_menu_music(audio: Res<AudioChannel<MusicChannel>>) {
+    let tween = AudioTween::new(Duration::from_millis(400), AudioEasing::Linear);
+    audio.stop().fade_out(tween);
+}
+
+fn stop_music_layers(
+    mut director: ResMut<MusicDirector>,
+    exploration: Res<AudioChannel<ExplorationChannel>>,
+    combat: Res<AudioChannel<CombatChannel>>,
+    boss: Res<AudioChannel<BossChannel>>,
+) {
+    exploration.stop();
+    combat.stop();
+    boss.stop();
+    //Next run starts every layer over from the top
+    *director = MusicDirector::default();
+}
+
+fn choose_music_layer(
+    mut director: ResMut<MusicDirector>,
+    enemies: Query<&AiStage, With<Enemy>>,
+    map: Res<MapDesc>,
+) {
+    let fighting = enemies
+        .iter()
+        .any(|stage| !matches!(stage, AiStage::Dieing(..)));
+    director.target = Some(match (fighting, map.is_boss_room()) {
+        (true, true) => MusicLayer::Boss,
+        (true, false) => MusicLayer::Combat,
+        (false, _) => MusicLayer::Exploration,
+    });
+}
+
+fn crossfade_music(
+    mut director: ResMut<MusicDirector>,
+    exploration: Res<AudioChannel<ExplorationChannel>>,
+    combat: Res<AudioChannel<CombatChannel>>,
+    boss: Res<AudioChannel<BossChannel>>,
     assets: Res<AssetServer>,
-    audio: Res<bevy_kira_audio::prelude::Audio>,
+    settings: Res<AudioSettings>,
+    //Real time, hit-stop shouldn't stretch out a fade
+    time: Res<Time>,
 ) {
-    let tween = AudioTween::new(Duration::from_millis(400), AudioEasing::Linear);
-    audio.stop().fade_out(tween.clone());
-    audio
-        .play(assets.load("Music/Swamp.wav"))
-        .looped()
-        .fade_in(tween);
+    let target = director.target;
+    let delta = time.delta_seconds();
+    let volume = settings.music_volume();
+    fade_layer(
+        &exploration,
+        &mut director.exploration,
+        target == Some(MusicLayer::Exploration),
+        "Music/Swamp.wav",
+        &assets,
+        volume,
+        delta,
+    );
+    fade_layer(
+        &combat,
+        &mut director.combat,
+        target == Some(MusicLayer::Combat),
+        //No dedicated fight track yet, borrow the boss one
+        "Music/Boss.wav",
+        &assets,
+        volume,
+        delta,
+    );
+    fade_layer(
+        &boss,
+        &mut director.boss,
+        target == Some(MusicLayer::Boss),
+        "Music/Boss.wav",
+        &assets,
+        volume,
+        delta,
+    );
+}
+
+fn fade_layer<T: Resource>(
+    channel: &AudioChannel<T>,
+    layer: &mut LayerState,
+    active: bool,
+    path: &str,
+    assets: &AssetServer,
+    volume: f64,
+    delta: f32,
+) {
+    if active {
+        if !layer.started {
+            channel.play(assets.load(path)).looped();
+            layer.started = true;
+        } else if layer.paused {
+            channel.resume();
+        }
+        layer.paused = false;
+        layer.volume = (layer.volume + delta / CROSSFADE_SECONDS).min(1.0);
+    } else if layer.started && !layer.paused {
+        layer.volume = (layer.volume - delta / CROSSFADE_SECONDS).max(0.0);
+        if layer.volume <= 0.0 {
+            channel.pause();
+            layer.paused = true;
+        }
+    }
+    channel.set_volume(layer.volume as f64 * volume);
 }
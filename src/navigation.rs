@@ -0,0 +1,70 @@
+use crate::prelude::*;
+
+pub struct NavigationPlugin;
+
+/// Where an enemy should actually walk to get to the player, filled in from the room's `NavGrid`
+#[derive(Component)]
+pub struct Navigation {
+    //Next point to head for when the player can't be walked to directly
+    pub waypoint: Option<Vec3>,
+    //Clear line to the player, enemies only start charging when this is true
+    pub can_reach: bool,
+    pub path: Vec<Vec2>,
+    pub repath_timer: Timer,
+}
+
+impl Default for Navigation {
+    fn default() -> Self {
+        Self {
+            waypoint: None,
+            can_reach: true,
+            path: Vec::default(),
+            repath_timer: Timer::from_seconds(0.5, true),
+        }
+    }
+}
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Main).with_system(navigate_enemies));
+    }
+}
+
+fn navigate_enemies(
+    grid: Option<Res<NavGrid>>,
+    mut enemies: Query<(&Transform, &mut Navigation), Without<Player>>,
+    player: Query<&Transform, With<Player>>,
+    time: Res<GameTime>,
+) {
+    let (grid, player) = match (grid, player.get_single()) {
+        (Some(grid), Ok(player)) => (grid, player.translation.truncate()),
+        _ => return,
+    };
+    for (transform, mut nav) in &mut enemies {
+        let position = transform.translation.truncate();
+        nav.can_reach = grid.line_of_sight(position, player);
+        if nav.can_reach {
+            nav.path.clear();
+            nav.waypoint = None;
+            continue;
+        }
+
+        nav.repath_timer.tick(time.delta());
+        if nav.repath_timer.just_finished() || nav.path.is_empty() {
+            nav.path = grid.find_path(position, player).unwrap_or_default();
+        }
+        //Skip any points we can already see past so movement doesn't zig zag cell to cell
+        while nav.path.len() > 1 && grid.line_of_sight(position, nav.path[1]) {
+            nav.path.remove(0);
+        }
+        if let Some(next) = nav.path.first() {
+            if next.distance(position) < grid.cell_size * 0.5 {
+                nav.path.remove(0);
+            }
+        }
+        nav.waypoint = nav
+            .path
+            .first()
+            .map(|point| point.extend(transform.translation.z));
+    }
+}
@@ -1,4 +1,11 @@
-use std::{collections::HashMap, f32::consts::PI, time::Duration};
+use std::{collections::HashSet, f32::consts::PI, time::Duration};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
 
 use crate::{inventory::Inventory, prelude::*};
 //use bevy::utils::HashMap;
@@ -17,8 +24,15 @@ pub struct Player {
     pub swing_radius: f32,
     pub swing_dir_vec2: Vec2,
     pub swing_direction: f32,
+    pub swing_reversed: bool,
     pub swinging: bool,
     pub swing_timer: Timer,
+    pub charging: bool,
+    pub charge_timer: Timer,
+    pub combo_step: usize,
+    pub combo_timer: Timer,
+    //From damage potions, scales every swing
+    pub damage_multiplier: f32,
 }
 
 #[derive(Component, Reflect, Default)]
@@ -26,6 +40,79 @@ pub struct Player {
 pub struct Sword {
     pub active: bool,
     pub damage: f32,
+    pub knockback: f32,
+    //Everything this swing has already hit, each enemy only takes one hit per swing
+    #[reflect(ignore)]
+    pub hit: HashSet<Entity>,
+}
+
+impl Sword {
+    pub fn begin_swing(&mut self, attack: &SwordAttack) {
+        self.damage = attack.damage;
+        self.knockback = attack.knockback;
+        self.hit.clear();
+    }
+}
+
+/// One entry in a moveset, everything about a single swing lives here
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct SwordAttack {
+    pub name: String,
+    //Radians swept over the whole swing
+    pub arc: f32,
+    pub damage: f32,
+    //Seconds the swing lasts
+    pub duration: f32,
+    //Speed the enemy gets launched away at
+    pub knockback: f32,
+    //Swing from the other side, for back-hands in the middle of a combo
+    #[serde(default)]
+    pub reversed: bool,
+}
+
+/// The attacks the player can chain together, loaded from `assets/Player/sword.moveset.ron` so
+/// swings can be tuned without a recompile. Light attacks follow `combo` in order as long as
+/// each press lands within `combo_window` of the last swing ending, holding swing for
+/// `charge_time` releases the heavy attack instead
+#[derive(Component, Deserialize, TypeUuid, Clone, Debug)]
+#[uuid = "ba95b3dd-9a0a-4238-862e-f61be8b1b1c6"]
+pub struct SwordMoveset {
+    pub combo: Vec<SwordAttack>,
+    pub heavy: SwordAttack,
+    pub combo_window: f32,
+    pub charge_time: f32,
+}
+
+impl SwordMoveset {
+    /// Which attack to do on release, heavy if charged long enough otherwise the next combo hit
+    pub fn next_attack(&self, combo_step: usize, charged: bool) -> &SwordAttack {
+        if charged || self.combo.is_empty() {
+            &self.heavy
+        } else {
+            &self.combo[combo_step % self.combo.len()]
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SwordMovesetLoader;
+
+impl AssetLoader for SwordMovesetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let moveset: SwordMoveset = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(moveset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["moveset.ron"]
+    }
 }
 
 #[derive(Component)]
@@ -35,6 +122,8 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Player>()
             .register_type::<Sword>()
+            .add_asset::<SwordMoveset>()
+            .init_asset_loader::<SwordMovesetLoader>()
             .add_system(player_movement)
             .add_system(sword_swing)
             .add_system(sword_updating)
@@ -67,6 +156,7 @@ fn player_hitbox_updating(mut player: Query<(&Player, &mut CollisionLayers, &mut
         if player.rolling {
             *collision = CollisionLayers::all_masks::<PhysicLayer>()
                 .without_mask(PhysicLayer::Enemy)
+                .without_mask(PhysicLayer::Projectile)
                 .with_group(PhysicLayer::Player);
         //*rigid = RigidBody::KinematicPositionBased;
         } else {
@@ -90,18 +180,56 @@ fn sword_updating(player: Query<&Player>, mut sword: Query<(&mut Sword, &mut Vis
     }
 }
 
+/// Sets up the player to swing with the given attack
+fn start_swing(player: &mut Player, attack: &SwordAttack) {
+    player.swinging = true;
+    player.swing_radius = attack.arc;
+    player.swing_reversed = attack.reversed;
+    player.swing_timer = Timer::from_seconds(attack.duration, false);
+}
+
 //PERF this query could have a with marker to not be so broad
 fn sword_swing(
-    mut player: Query<(&Children, &mut Player, &ActionState<Action>)>,
+    mut player: Query<(&Children, &mut Player, &SwordMoveset, &ActionState<Action>)>,
     mut transforms: Query<(&mut Transform, &GlobalTransform), With<SwordParent>>,
+    mut sword: Query<&mut Sword>,
     mouse: Res<MousePos>,
-    time: Res<Time>,
+    mut sfx: EventWriter<SfxEvent>,
+    time: Res<GameTime>,
 ) {
-    for (children, mut player, action) in &mut player {
-        // Handle starting swing
-        if !player.swinging && action.just_pressed(Action::Swing) {
-            player.swinging = true;
-            player.swing_timer.set_elapsed(Duration::from_secs(0));
+    for (children, mut player, moveset, action) in &mut player {
+        // Handle starting a charge, the swing itself happens on release
+        // Pressing mid swing is kept so the next combo hit comes out as soon as this one ends
+        if action.just_pressed(Action::Swing) {
+            player.charging = true;
+            player.charge_timer = Timer::from_seconds(moveset.charge_time, false);
+        }
+        if !player.swinging {
+            //Combo drops if the window after the last swing runs out
+            player.combo_timer.tick(time.delta());
+            if player.combo_timer.just_finished() {
+                player.combo_step = 0;
+            }
+
+            if player.charging {
+                player.charge_timer.tick(time.delta());
+                if action.released(Action::Swing) {
+                    player.charging = false;
+                    let charged = player.charge_timer.finished();
+                    let attack = moveset.next_attack(player.combo_step, charged).clone();
+                    if charged {
+                        player.combo_step = 0;
+                    } else {
+                        player.combo_step = (player.combo_step + 1) % moveset.combo.len().max(1);
+                    }
+                    start_swing(&mut player, &attack);
+                    sfx.send(SfxEvent(Sfx::Swing));
+                    for mut sword in &mut sword {
+                        sword.begin_swing(&attack);
+                        sword.damage *= player.damage_multiplier;
+                    }
+                }
+            }
         }
 
         //If there are more than 1 child this needs rework
@@ -112,10 +240,16 @@ fn sword_swing(
                 player.swing_timer.tick(time.delta());
                 if player.swing_timer.just_finished() {
                     player.swinging = false;
+                    player.combo_timer = Timer::from_seconds(moveset.combo_window, false);
                 } else if let Ok((mut transform, _global)) = transforms.get_mut(*child) {
+                    let progress = if player.swing_reversed {
+                        player.swing_timer.percent_left()
+                    } else {
+                        player.swing_timer.percent()
+                    };
                     transform.rotation = Quat::from_axis_angle(
                         Vec3::Z,
-                        player.swing_direction + player.swing_radius * player.swing_timer.percent(),
+                        player.swing_direction + player.swing_radius * progress,
                     );
                 }
             //Otherwise match mouse angle with a bit of an offset and record it
@@ -124,9 +258,11 @@ fn sword_swing(
                 if direction == Vec2::ZERO {
                     direction = Vec2::splat(0.001);
                 }
+                //Aim with the arc of whatever attack would come out next
+                let charged = player.charging && player.charge_timer.finished();
+                let arc = moveset.next_attack(player.combo_step, charged).arc;
                 player.swing_dir_vec2 = direction;
-                player.swing_direction =
-                    -player.swing_radius / 2.0 - direction.angle_between(Vec2::Y);
+                player.swing_direction = -arc / 2.0 - direction.angle_between(Vec2::Y);
                 transform.rotation = Quat::from_axis_angle(Vec3::Z, player.swing_direction);
             }
         }
@@ -135,7 +271,8 @@ fn sword_swing(
 
 fn player_dodge_roll(
     mut player: Query<(&mut Player, &mut Transform, &ActionState<Action>)>,
-    time: Res<Time>,
+    mut sfx: EventWriter<SfxEvent>,
+    time: Res<GameTime>,
 ) {
     if let Ok((mut player, mut transform, input)) = player.get_single_mut() {
         //Check input to init roll, also don't roll if no direction
@@ -143,6 +280,7 @@ fn player_dodge_roll(
             if input.just_pressed(Action::Roll) && player.roll_direction != Vec3::ZERO {
                 player.rolling = true;
                 player.roll_timer.set_elapsed(Duration::from_secs(0));
+                sfx.send(SfxEvent(Sfx::Roll));
             } else {
                 // Not rolling and not pressing the roll key
                 return;
@@ -171,7 +309,7 @@ fn player_dodge_roll(
 
 pub fn player_movement(
     mut player: Query<(&mut Player, &mut Transform, &ActionState<Action>)>,
-    time: Res<Time>,
+    time: Res<GameTime>,
 ) {
     //I'd kinda perfer to crash if theres multiple players but adding a crash isn't that important
     if let Ok((mut player, mut transform, input)) = player.get_single_mut() {
@@ -203,7 +341,34 @@ pub fn player_movement(
     }
 }
 
-fn spawn_player(mut commands: Commands, assets: Res<GameAssets>, controls: Res<ControlSettings>) {
+fn spawn_player(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    movesets: Res<Assets<SwordMoveset>>,
+    controls: Res<ControlSettings>,
+    profile: Res<Profile>,
+) {
+    //Loaded with the rest of the game assets before the menu shows up
+    let moveset = movesets
+        .get(&assets.sword_moveset)
+        .expect("Sword moveset not loaded")
+        .clone();
+    let mut inventory = Inventory::new();
+    if profile.has(Upgrade::BiggerBelt) {
+        for cap in inventory.caps.values_mut() {
+            *cap += 3;
+        }
+    }
+    if profile.has(Upgrade::StartingIngredients) {
+        for ingredient in [Ingredient::FrogEyes, Ingredient::BatWings] {
+            inventory.items.insert(ingredient, 2);
+        }
+    }
+    let health = if profile.has(Upgrade::ExtraHeart) {
+        4.
+    } else {
+        3.
+    };
     commands
         .spawn_bundle(SpriteSheetBundle {
             sprite: TextureAtlasSprite { ..default() },
@@ -211,30 +376,38 @@ fn spawn_player(mut commands: Commands, assets: Res<GameAssets>, controls: Res<C
             transform: Transform::from_xyz(0.0, 0.0, 5.0).with_scale(Vec3::splat(2.5)),
             ..default()
         })
-        .insert(Inventory {
-            items: HashMap::default(),
-        })
+        .insert(inventory)
         .insert(Player {
             speed: 200.0,
             roll_speed: 700.0,
             roll_direction: Vec3::ZERO,
             rolling: false,
             roll_timer: Timer::from_seconds(0.4, true),
-            swing_radius: 1.5 * PI / 2.0,
+            swing_radius: 0.0,
             swing_direction: 0.0,
             swing_dir_vec2: Vec2::splat(0.0),
+            swing_reversed: false,
             swinging: false,
-            swing_timer: Timer::from_seconds(0.35, true),
+            swing_timer: Timer::default(),
+            charging: false,
+            charge_timer: Timer::default(),
+            combo_step: 0,
+            combo_timer: Timer::default(),
+            damage_multiplier: 1.0,
         })
+        .insert(moveset)
         .insert(Animation {
             current_frame: 0,
             timer: Timer::from_seconds(0.15, true),
         })
         .insert(Health {
-            health: 3.,
+            health,
+            max_health: health,
             flashing: false,
             damage_flash_timer: Timer::from_seconds(1.0, true),
             damage_flash_times_per_hit: 5,
+            iframe_time: 1.0,
+            invulnerable: 0.0,
         })
         .insert_bundle(InputManagerBundle::<Action> {
             input_map: controls.input.clone(),
@@ -248,6 +421,9 @@ fn spawn_player(mut commands: Commands, assets: Res<GameAssets>, controls: Res<C
         .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::Player))
         .insert(RotationConstraints::lock())
         .insert(RigidBody::Dynamic)
+        //Pickups check what the player is standing on every frame
+        .insert(Collisions::default())
+        .insert(Velocity::default())
         .insert(Damping::from_linear(20.5).with_angular(0.2))
         .with_children(|commands| {
             commands
@@ -278,8 +454,11 @@ fn spawn_player(mut commands: Commands, assets: Res<GameAssets>, controls: Res<C
                         )
                         .insert(Sword {
                             active: false,
-                            damage: 10.0,
+                            damage: 0.0,
+                            knockback: 0.0,
+                            hit: HashSet::default(),
                         })
+                        .insert(Collisions::default())
                         .insert(Name::new("Sword"));
                 });
         });
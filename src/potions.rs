@@ -8,7 +8,9 @@ pub enum Potion {
 }
 
 impl Potion {
-    pub fn new(ingredients: (Ingredient, Ingredient)) -> Potion {
+    pub fn new(ingredients: (Ingredient, Ingredient), profile: &Profile) -> Potion {
+        //Two commons make a strength 1 potion, rarer parts brew stronger ones
+        let strength = ingredients.0.rarity().strength() + ingredients.1.rarity().strength() - 1;
         let bat_drops = vec![
             Ingredient::BatWings,
             Ingredient::BatEyes,
@@ -19,12 +21,30 @@ impl Potion {
             Ingredient::FrogLungs,
             Ingredient::FrogLegs,
         ];
-        if (bat_drops.contains(&ingredients.0) && frog_drops.contains(&ingredients.1))
+        let tough_drops = vec![
+            Ingredient::ToadSlime,
+            Ingredient::ToadWarts,
+            Ingredient::TurtleShell,
+            Ingredient::TurtleHeart,
+        ];
+        if profile.has(Upgrade::DamageRecipe)
+            && (tough_drops.contains(&ingredients.0) || tough_drops.contains(&ingredients.1))
+        {
+            Potion::Damage(strength)
+        } else if (bat_drops.contains(&ingredients.0) && frog_drops.contains(&ingredients.1))
             || (frog_drops.contains(&ingredients.0) && bat_drops.contains(&ingredients.1))
         {
-            Potion::Health(1)
+            Potion::Health(strength)
         } else {
-            Potion::Speed(1)
+            Potion::Speed(strength)
+        }
+    }
+    pub fn color(&self) -> Color {
+        match self {
+            Potion::Speed(_) => Color::YELLOW,
+            Potion::Damage(_) => Color::ORANGE_RED,
+            Potion::Health(_) => Color::PINK,
+            Potion::Other(_) => Color::PURPLE,
         }
     }
     pub fn consume(&self, player: &mut Player, health: &mut Health) {
@@ -32,11 +52,15 @@ impl Potion {
             Potion::Speed(strength) => {
                 player.speed = player.speed * (1.0 + (0.05 * *strength as f32));
             }
-            //Potion::Damage(strength) => {
-            //sword.damage = sword.damage * (1.0 + (0.3 * *strength as f32));
-            //}
+            Potion::Damage(strength) => {
+                player.damage_multiplier *= 1.0 + (0.3 * *strength as f32);
+            }
             Potion::Health(strength) => {
-                health.health = health.health + (1 * strength) as f32;
+                //Really strong brews grow a whole new heart before healing
+                if *strength >= 4 {
+                    health.set_max_health(health.max_health + 1.0);
+                }
+                health.heal(*strength as f32);
             }
             _ => {}
         }
@@ -1,15 +1,32 @@
 pub use bevy::prelude::*;
 
+pub use crate::ai::*;
 pub use crate::animation::*;
+pub use crate::camera::*;
+pub use crate::combat::*;
+pub use crate::display::*;
+pub use crate::doors::*;
+pub use crate::effects::*;
+pub use crate::elite::*;
 pub use crate::enemy::*;
+pub use crate::enemy_def::*;
 pub use crate::health::*;
+pub use crate::health_bar::*;
 pub use crate::ingredients::*;
 pub use crate::ingredients::*;
 pub use crate::input::*;
+pub use crate::map::nav::*;
+pub use crate::map::room::*;
 pub use crate::map::*;
 pub use crate::mouse::*;
 pub use crate::music::*;
+pub use crate::navigation::*;
 pub use crate::player::*;
+pub use crate::profile::*;
+pub use crate::projectile::*;
+pub use crate::save::*;
+pub use crate::settings::*;
+pub use crate::sfx::*;
 pub use crate::world_gen::*;
 pub use crate::*;
 
@@ -30,4 +47,5 @@ pub enum PhysicLayer {
     Sword,
     Enemy,
     Ingredients,
+    Projectile,
 }
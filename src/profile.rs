@@ -0,0 +1,193 @@
+use std::{collections::HashSet, fs};
+
+use bevy_inspector_egui::bevy_egui::{
+    egui::{Align2, Button, Grid, Window},
+    EguiContext,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::prelude::*;
+
+pub struct ProfilePlugin;
+
+const PROFILE_PATH: &str = "profile.ron";
+//Kills come in bursts, wait for them to settle before writing
+const SAVE_DELAY: f32 = 1.0;
+
+/// Permanent unlocks bought with essence between runs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq, EnumIter)]
+pub enum Upgrade {
+    ExtraHeart,
+    //Raises every ingredient cap
+    BiggerBelt,
+    StartingIngredients,
+    //Toad and turtle parts brew damage potions
+    DamageRecipe,
+}
+
+impl Upgrade {
+    pub fn cost(&self) -> u32 {
+        match self {
+            Upgrade::ExtraHeart => 40,
+            Upgrade::BiggerBelt => 25,
+            Upgrade::StartingIngredients => 20,
+            Upgrade::DamageRecipe => 60,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Upgrade::ExtraHeart => "Extra Heart",
+            Upgrade::BiggerBelt => "Bigger Potion Belt",
+            Upgrade::StartingIngredients => "Starting Ingredients",
+            Upgrade::DamageRecipe => "Recipe: Damage Potion",
+        }
+    }
+}
+
+/// Everything that carries over between runs, kept in `profile.ron`
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Profile {
+    pub essence: u32,
+    pub upgrades: HashSet<Upgrade>,
+}
+
+impl Profile {
+    fn load() -> Self {
+        match fs::read_to_string(PROFILE_PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("Ignoring bad {}: {}", PROFILE_PATH, err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(fs::write(PROFILE_PATH, contents)?));
+        if let Err(err) = result {
+            warn!("Failed to save {}: {}", PROFILE_PATH, err);
+        }
+    }
+
+    pub fn has(&self, upgrade: Upgrade) -> bool {
+        self.upgrades.contains(&upgrade)
+    }
+
+    /// Returns false if it's already owned or there isn't enough essence
+    pub fn buy(&mut self, upgrade: Upgrade) -> bool {
+        if self.has(upgrade) || self.essence < upgrade.cost() {
+            return false;
+        }
+        self.essence -= upgrade.cost();
+        self.upgrades.insert(upgrade);
+        true
+    }
+}
+
+/// Whether the upgrade window is showing, opened from the start menu
+#[derive(Default)]
+pub struct UpgradeMenu {
+    pub open: bool,
+}
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profile::load())
+            .insert_resource(UpgradeMenu::default())
+            .add_system(earn_essence)
+            .add_system(save_profile_when_changed.after(earn_essence))
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(upgrade_window))
+            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(close_upgrade_window))
+            //Flush anything still waiting on the save delay when the run ends
+            .add_system_set(SystemSet::on_exit(GameState::Main).with_system(save_profile));
+    }
+}
+
+fn earn_essence(
+    mut deaths: EventReader<DeathEvent>,
+    enemies: Query<Option<&Elite>, With<Enemy>>,
+    mut profile: ResMut<Profile>,
+) {
+    for death in deaths.iter() {
+        if let Ok(elite) = enemies.get(death.0) {
+            profile.essence += if elite.is_some() { 3 } else { 1 };
+        }
+    }
+}
+
+fn save_profile(profile: Res<Profile>) {
+    profile.save();
+}
+
+/// Keeps essence on disk so closing the game mid-room doesn't lose kills
+fn save_profile_when_changed(
+    profile: Res<Profile>,
+    mut pending: Local<Option<Timer>>,
+    time: Res<Time>,
+) {
+    if profile.is_changed() && !profile.is_added() {
+        *pending = Some(Timer::from_seconds(SAVE_DELAY, false));
+    }
+    if let Some(timer) = pending.as_mut() {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            profile.save();
+            *pending = None;
+        }
+    }
+}
+
+fn close_upgrade_window(mut menu: ResMut<UpgradeMenu>) {
+    menu.open = false;
+}
+
+fn upgrade_window(
+    mut egui: ResMut<EguiContext>,
+    mut menu: ResMut<UpgradeMenu>,
+    mut profile: ResMut<Profile>,
+) {
+    if !menu.open {
+        return;
+    }
+    let mut open = menu.open;
+    let mut bought = None;
+    Window::new("Upgrades")
+        .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(egui.ctx_mut(), |ui| {
+            ui.label(format!("Essence: {}", profile.essence));
+            Grid::new("Upgrade grid").striped(true).show(ui, |ui| {
+                for upgrade in Upgrade::iter() {
+                    ui.label(upgrade.name());
+                    if profile.has(upgrade) {
+                        ui.label("Owned");
+                    } else {
+                        let affordable = profile.essence >= upgrade.cost();
+                        let button = ui.add_enabled(
+                            affordable,
+                            Button::new(format!("Buy ({})", upgrade.cost())),
+                        );
+                        if button.clicked() {
+                            bought = Some(upgrade);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    menu.open = open;
+    if let Some(upgrade) = bought {
+        if profile.buy(upgrade) {
+            profile.save();
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use crate::prelude::*;
+
+pub struct ProjectilePlugin;
+
+/// Something flying through the air that hurts whatever it hits, the player can knock them back
+/// at enemies with a swing
+#[derive(Component)]
+pub struct Projectile {
+    pub owner: Entity,
+    pub damage: f32,
+    //Knocked back by the sword, now hurts enemies instead of the player
+    pub deflected: bool,
+    pub lifetime: Timer,
+}
+
+const DEFLECT_SPEEDUP: f32 = 1.5;
+//Spit is weak against the player but a good return shot should hurt, enemies have far more health
+const DEFLECT_DAMAGE_MULTIPLIER: f32 = 10.0;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(projectile_lifetime)
+            .add_system(deflect_projectiles)
+            .add_system(projectile_collision.after(deflect_projectiles));
+    }
+}
+
+pub fn spawn_projectile(
+    commands: &mut Commands,
+    owner: Entity,
+    position: Vec3,
+    velocity: Vec2,
+    damage: f32,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.5, 0.9, 0.2),
+                custom_size: Some(Vec2::splat(20.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.truncate().extend(20.0)),
+            ..default()
+        })
+        .insert(Projectile {
+            owner,
+            damage,
+            deflected: false,
+            lifetime: Timer::from_seconds(4.0, false),
+        })
+        //Dynamic with a sensor shape so it still reports hitting static walls
+        .insert(RigidBody::Dynamic)
+        .insert(CollisionShape::Sphere { radius: 12.0 })
+        .insert(SensorShape)
+        .insert(RotationConstraints::lock())
+        .insert(Velocity::from_linear(velocity.extend(0.0)))
+        .insert(
+            CollisionLayers::none()
+                .with_group(PhysicLayer::Projectile)
+                .with_masks(&[
+                    PhysicLayer::World,
+                    PhysicLayer::Player,
+                    PhysicLayer::Sword,
+                    PhysicLayer::Enemy,
+                ]),
+        )
+        .insert(RoomMember)
+        .insert(Name::new("Projectile"));
+}
+
+fn projectile_lifetime(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Projectile)>,
+    time: Res<GameTime>,
+) {
+    for (ent, mut projectile) in &mut projectiles {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.just_finished() {
+            commands.entity(ent).despawn_recursive();
+        }
+    }
+}
+
+/// Checks what the sword is overlapping every frame so a spit already touching it when the swing
+/// starts still gets knocked back
+fn deflect_projectiles(
+    swords: Query<(&Sword, &Collisions)>,
+    mut projectiles: Query<(
+        &mut Projectile,
+        &mut Velocity,
+        &mut Sprite,
+        &GlobalTransform,
+    )>,
+    enemies: Query<&GlobalTransform, With<Enemy>>,
+) {
+    for (sword, collisions) in &swords {
+        //Only a swing knocks it back, running into the sword does nothing
+        if !sword.active {
+            continue;
+        }
+        for ent in collisions.entities() {
+            let (mut projectile, mut velocity, mut sprite, transform) =
+                match projectiles.get_mut(ent) {
+                    Ok(projectile) => projectile,
+                    Err(_) => continue,
+                };
+            if projectile.deflected {
+                continue;
+            }
+            projectile.deflected = true;
+            let speed = velocity.linear.length() * DEFLECT_SPEEDUP;
+            //Send it back where it came from if the spitter is still around
+            let direction = match enemies.get(projectile.owner) {
+                Ok(owner) => (owner.translation() - transform.translation())
+                    .truncate()
+                    .normalize_or_zero(),
+                Err(_) => -velocity.linear.truncate().normalize_or_zero(),
+            };
+            velocity.linear = (direction * speed).extend(0.0);
+            sprite.color = Color::rgb(0.9, 0.9, 0.6);
+        }
+    }
+}
+
+fn projectile_collision(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    projectiles: Query<(&Projectile, &Sprite, &GlobalTransform)>,
+    players: Query<(), With<Player>>,
+    enemies: Query<(), With<Enemy>>,
+    mut damage: EventWriter<DamageEvent>,
+    mut particles: EventWriter<ParticleEvent>,
+) {
+    for event in collision_events.iter() {
+        if let CollisionEvent::Started(d1, d2) = event {
+            for (projectile_data, other_data) in [(d1, d2), (d2, d1)] {
+                let projectile_ent = projectile_data.rigid_body_entity();
+                let other = other_data.rigid_body_entity();
+                let (projectile, sprite, transform) = match projectiles.get(projectile_ent) {
+                    Ok(projectile) => projectile,
+                    Err(_) => continue,
+                };
+
+                let mut hit = false;
+                if players.contains(other) {
+                    if !projectile.deflected {
+                        damage.send(DamageEvent {
+                            source: projectile_ent,
+                            target: other,
+                            amount: projectile.damage,
+                            kind: DamageKind::Projectile,
+                            knockback: 600.0,
+                        });
+                        hit = true;
+                    }
+                } else if enemies.contains(other) {
+                    if projectile.deflected {
+                        damage.send(DamageEvent {
+                            source: projectile_ent,
+                            target: other,
+                            amount: projectile.damage * DEFLECT_DAMAGE_MULTIPLIER,
+                            kind: DamageKind::Projectile,
+                            knockback: 600.0,
+                        });
+                        hit = true;
+                    }
+                } else if other_data
+                    .collision_layers()
+                    .contains_group(PhysicLayer::World)
+                {
+                    hit = true;
+                }
+
+                if hit {
+                    particles.send(ParticleEvent {
+                        position: transform.translation(),
+                        color: sprite.color,
+                        count: 6,
+                        speed: 150.0,
+                    });
+                    commands.entity(projectile_ent).despawn_recursive();
+                }
+            }
+        }
+    }
+}
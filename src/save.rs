@@ -0,0 +1,129 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{inventory::Inventory, prelude::*};
+
+pub struct SavePlugin;
+
+const SAVE_PATH: &str = "run.ron";
+
+/// Snapshot of a run in progress, written every time the player changes rooms
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RunSave {
+    pub map: MapDesc,
+    pub position: (f32, f32),
+    pub health: f32,
+    //Older saves don't have this, the spawned player's max is kept then
+    #[serde(default)]
+    pub max_health: Option<f32>,
+    pub speed: f32,
+    pub roll_speed: f32,
+    #[serde(default = "no_bonus")]
+    pub damage_multiplier: f32,
+    pub inventory: HashMap<Ingredient, usize>,
+}
+
+fn no_bonus() -> f32 {
+    1.0
+}
+
+impl RunSave {
+    pub fn exists() -> bool {
+        Path::new(SAVE_PATH).exists()
+    }
+
+    pub fn load() -> Option<Self> {
+        let contents = fs::read_to_string(SAVE_PATH).ok()?;
+        ron::from_str(&contents)
+            .map_err(|err| warn!("Ignoring bad {}: {}", SAVE_PATH, err))
+            .ok()
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(fs::write(SAVE_PATH, contents)?));
+        if let Err(err) = result {
+            warn!("Failed to save {}: {}", SAVE_PATH, err);
+        }
+    }
+
+    /// Runs are one life only, dying or starting over throws the save away
+    pub fn delete() {
+        if RunSave::exists() {
+            if let Err(err) = fs::remove_file(SAVE_PATH) {
+                warn!("Failed to delete {}: {}", SAVE_PATH, err);
+            }
+        }
+    }
+}
+
+/// Inserted by the Continue button, applied to the player once they've spawned
+pub struct ContinueRun(pub RunSave);
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Main).with_system(apply_run_save))
+            .add_system(delete_save_on_death)
+            //PostUpdate so load_next_room, which runs after the fade that sends the exit, has
+            //already set the new room and the player's entry point
+            .add_system_to_stage(CoreStage::PostUpdate, save_run);
+    }
+}
+
+fn save_run(
+    mut exits: EventReader<ExitEvent>,
+    map: Res<MapDesc>,
+    player: Query<(&Transform, &Health, &Player, &Inventory)>,
+) {
+    if exits.iter().last().is_none() {
+        return;
+    }
+    if let Ok((transform, health, player, inventory)) = player.get_single() {
+        RunSave {
+            map: map.clone(),
+            position: (transform.translation.x, transform.translation.y),
+            health: health.health,
+            max_health: Some(health.max_health),
+            speed: player.speed,
+            roll_speed: player.roll_speed,
+            damage_multiplier: player.damage_multiplier,
+            inventory: inventory.items.clone(),
+        }
+        .save();
+    }
+}
+
+fn apply_run_save(
+    mut commands: Commands,
+    save: Option<Res<ContinueRun>>,
+    mut player: Query<(&mut Transform, &mut Health, &mut Player, &mut Inventory)>,
+) {
+    let save = match save {
+        Some(save) => save,
+        None => return,
+    };
+    //Player spawns through commands on entering Main so it may not be here yet
+    if let Ok((mut transform, mut health, mut player, mut inventory)) = player.get_single_mut() {
+        let save = &save.0;
+        transform.translation.x = save.position.0;
+        transform.translation.y = save.position.1;
+        if let Some(max_health) = save.max_health {
+            health.max_health = max_health;
+        }
+        health.health = save.health.min(health.max_health);
+        player.speed = save.speed;
+        player.roll_speed = save.roll_speed;
+        player.damage_multiplier = save.damage_multiplier;
+        inventory.items = save.inventory.clone();
+        commands.remove_resource::<ContinueRun>();
+    }
+}
+
+fn delete_save_on_death(mut deaths: EventReader<DeathEvent>, player: Query<(), With<Player>>) {
+    if deaths.iter().any(|death| player.contains(death.0)) {
+        RunSave::delete();
+    }
+}
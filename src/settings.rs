@@ -0,0 +1,203 @@
+use std::fs;
+
+use bevy_inspector_egui::bevy_egui::{
+    egui::{Align2, Slider, Window},
+    EguiContext,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+pub struct SettingsPlugin;
+
+//Written next to the executable, same as where the game is run from
+const SETTINGS_PATH: &str = "settings.ron";
+//Wait for sliders to settle before writing so dragging doesn't hit the disk every frame
+const SAVE_DELAY: f32 = 1.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.8,
+            sfx: 0.8,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn music_volume(&self) -> f64 {
+        if self.muted {
+            0.0
+        } else {
+            (self.master * self.music) as f64
+        }
+    }
+
+    pub fn sfx_volume(&self) -> f64 {
+        if self.muted {
+            0.0
+        } else {
+            (self.master * self.sfx) as f64
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub fullscreen: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GameplaySettings {
+    //Exits stay shut until every enemy in the room is dead
+    pub lock_doors: bool,
+}
+
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self { lock_doors: true }
+    }
+}
+
+/// Everything that gets saved to `settings.ron`, missing sections fall back to defaults so old
+/// files keep loading
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct SettingsFile {
+    audio: AudioSettings,
+    video: VideoSettings,
+    gameplay: GameplaySettings,
+}
+
+impl SettingsFile {
+    fn load() -> Self {
+        match fs::read_to_string(SETTINGS_PATH) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("Ignoring bad {}: {}", SETTINGS_PATH, err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(fs::write(SETTINGS_PATH, contents)?));
+        if let Err(err) = result {
+            warn!("Failed to save {}: {}", SETTINGS_PATH, err);
+        }
+    }
+}
+
+/// Whether the settings window is showing, toggled with Escape or the start menu button
+#[derive(Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let file = SettingsFile::load();
+        app.insert_resource(file.audio)
+            .insert_resource(file.video)
+            .insert_resource(file.gameplay)
+            .insert_resource(SettingsMenu::default())
+            .add_system(toggle_settings_menu)
+            .add_system(settings_window)
+            .add_system(save_settings);
+    }
+}
+
+fn toggle_settings_menu(keyboard: Res<Input<KeyCode>>, mut menu: ResMut<SettingsMenu>) {
+    if keyboard.just_pressed(KeyCode::Escape) {
+        menu.open = !menu.open;
+    }
+}
+
+fn settings_window(
+    mut egui: ResMut<EguiContext>,
+    mut menu: ResMut<SettingsMenu>,
+    mut audio: ResMut<AudioSettings>,
+    mut video: ResMut<VideoSettings>,
+    mut gameplay: ResMut<GameplaySettings>,
+) {
+    if !menu.open {
+        return;
+    }
+    //Work on a copy so the resource is only marked changed when something actually moved
+    let mut edited = audio.clone();
+    let mut fullscreen = video.fullscreen;
+    let mut lock_doors = gameplay.lock_doors;
+    let mut open = menu.open;
+    Window::new("Settings")
+        .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(egui.ctx_mut(), |ui| {
+            ui.add(Slider::new(&mut edited.master, 0.0..=1.0).text("Master"));
+            ui.add(Slider::new(&mut edited.music, 0.0..=1.0).text("Music"));
+            ui.add(Slider::new(&mut edited.sfx, 0.0..=1.0).text("Sound Effects"));
+            ui.checkbox(&mut edited.muted, "Mute");
+            ui.separator();
+            ui.checkbox(&mut fullscreen, "Fullscreen (F11)");
+            ui.separator();
+            ui.checkbox(&mut lock_doors, "Lock doors during fights");
+        });
+    menu.open = open;
+    if edited.master != audio.master
+        || edited.music != audio.music
+        || edited.sfx != audio.sfx
+        || edited.muted != audio.muted
+    {
+        *audio = edited;
+    }
+    if fullscreen != video.fullscreen {
+        video.fullscreen = fullscreen;
+    }
+    if lock_doors != gameplay.lock_doors {
+        gameplay.lock_doors = lock_doors;
+    }
+}
+
+fn save_settings(
+    audio: Res<AudioSettings>,
+    video: Res<VideoSettings>,
+    gameplay: Res<GameplaySettings>,
+    mut pending: Local<Option<Timer>>,
+    time: Res<Time>,
+) {
+    let edited = (audio.is_changed() && !audio.is_added())
+        || (video.is_changed() && !video.is_added())
+        || (gameplay.is_changed() && !gameplay.is_added());
+    if edited {
+        *pending = Some(Timer::from_seconds(SAVE_DELAY, false));
+    }
+    if let Some(timer) = pending.as_mut() {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            SettingsFile {
+                audio: audio.clone(),
+                video: video.clone(),
+                gameplay: gameplay.clone(),
+            }
+            .save();
+            *pending = None;
+        }
+    }
+}
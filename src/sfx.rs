@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use bevy_kira_audio::prelude::*;
+use rand::Rng;
+
+use crate::prelude::*;
+
+pub struct SfxPlugin;
+
+/// Kira channel for every sound effect so they can be controlled apart from the music
+pub struct SfxChannel;
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Sfx {
+    Roll,
+    Swing,
+    FrogJump,
+    FrogLand,
+    Hit,
+    PlayerHit,
+    Pickup,
+}
+
+impl Sfx {
+    fn path(&self) -> &'static str {
+        match self {
+            Sfx::Roll => "Sfx/Swamp_dash_v0.0.0.1.mp3",
+            Sfx::Swing => "Sfx/Sword whoosh empty.mp3",
+            Sfx::FrogJump | Sfx::Pickup => "Sfx/frog_boing_v0.0.mp3",
+            Sfx::FrogLand => "Sfx/frog_landing_v0.0.mp3",
+            //TODO real hit sounds, a low squelch will do for now
+            Sfx::Hit | Sfx::PlayerHit => "Sfx/swamp_foot.mp3",
+        }
+    }
+
+    //Minimum seconds between two plays of the same sound, stops a room of frogs all landing at
+    //once from being deafening
+    fn cooldown(&self) -> f32 {
+        match self {
+            Sfx::FrogJump | Sfx::FrogLand => 0.15,
+            Sfx::Hit | Sfx::Pickup => 0.05,
+            Sfx::Roll | Sfx::Swing | Sfx::PlayerHit => 0.0,
+        }
+    }
+
+    fn playback_rate(&self) -> f64 {
+        match self {
+            Sfx::Pickup => 1.5,
+            Sfx::Hit => 0.8,
+            Sfx::PlayerHit => 0.6,
+            _ => 1.0,
+        }
+    }
+
+    fn volume(&self) -> f64 {
+        match self {
+            Sfx::FrogJump | Sfx::FrogLand | Sfx::Pickup => 0.5,
+            _ => 0.8,
+        }
+    }
+}
+
+/// Anything that wants to make a noise sends one of these
+#[derive(Clone, Copy)]
+pub struct SfxEvent(pub Sfx);
+
+//Random +- on playback rate so repeated sounds don't get grating
+const PITCH_VARIANCE: f64 = 0.08;
+
+/// Seconds left before each sound can play again
+#[derive(Default)]
+pub struct SfxCooldowns(HashMap<Sfx, f32>);
+
+impl Plugin for SfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_audio_channel::<SfxChannel>()
+            .add_event::<SfxEvent>()
+            .insert_resource(SfxCooldowns::default())
+            .add_system_to_stage(CoreStage::PostUpdate, play_sfx);
+    }
+}
+
+fn play_sfx(
+    mut events: EventReader<SfxEvent>,
+    mut cooldowns: ResMut<SfxCooldowns>,
+    channel: Res<AudioChannel<SfxChannel>>,
+    assets: Res<AssetServer>,
+    settings: Res<AudioSettings>,
+    //Real time so hit-stop doesn't hold cooldowns open
+    time: Res<Time>,
+) {
+    for remaining in cooldowns.0.values_mut() {
+        *remaining -= time.delta_seconds();
+    }
+    let mut rng = rand::thread_rng();
+    for SfxEvent(sfx) in events.iter() {
+        let remaining = cooldowns.0.entry(*sfx).or_insert(0.0);
+        if *remaining > 0.0 {
+            continue;
+        }
+        *remaining = sfx.cooldown();
+        let rate = sfx.playback_rate() * rng.gen_range(1.0 - PITCH_VARIANCE..1.0 + PITCH_VARIANCE);
+        channel
+            .play(assets.load(sfx.path()))
+            .with_playback_rate(rate)
+            .with_volume(sfx.volume() * settings.sfx_volume());
+    }
+}
@@ -13,18 +13,51 @@ const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
+#[derive(Component, Clone, Copy)]
+pub enum MenuButton {
+    NewRun,
+    Continue,
+    Upgrades,
+    Settings,
+}
+
 fn start_button(
+    mut commands: Commands,
     mut interaction_query: Query<
-        (&Interaction, &mut UiColor, &Children),
+        (&Interaction, &mut UiColor, &MenuButton),
         (Changed<Interaction>, With<Button>),
     >,
     mut game_state: ResMut<State<GameState>>,
+    mut map: ResMut<MapDesc>,
+    mut upgrades: ResMut<UpgradeMenu>,
+    mut settings: ResMut<SettingsMenu>,
 ) {
-    for (interaction, mut color, children) in &mut interaction_query {
+    for (interaction, mut color, button) in &mut interaction_query {
         match *interaction {
             Interaction::Clicked => {
-                game_state.set(GameState::Main).unwrap();
                 *color = PRESSED_BUTTON.into();
+                match button {
+                    MenuButton::Upgrades => {
+                        upgrades.open = !upgrades.open;
+                        continue;
+                    }
+                    MenuButton::Settings => {
+                        settings.open = !settings.open;
+                        continue;
+                    }
+                    _ => {}
+                }
+                match (button, RunSave::load()) {
+                    (MenuButton::Continue, Some(save)) => {
+                        *map = save.map.clone();
+                        commands.insert_resource(ContinueRun(save));
+                    }
+                    _ => {
+                        *map = MapDesc::new_run();
+                        RunSave::delete();
+                    }
+                }
+                game_state.set(GameState::Main).unwrap();
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -61,45 +94,66 @@ fn spawn_start_menu(mut commands: Commands, assets: Res<AssetServer>) {
         })
         .insert(MainMenu)
         .with_children(|parent| {
-            parent.spawn_bundle(TextBundle::from_section(
-                "WitchBrew",
-                TextStyle {
-                    font: assets.load("Font/DancingScript-VariableFont_wght.ttf"),
-                    font_size: 128.0,
-                    color: Color::rgb(0.2, 0.2, 0.2),
-                },
-            ));
             parent
-                .spawn_bundle(ButtonBundle {
-                    style: Style {
-                        align_self: AlignSelf::Center,
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        padding: UiRect::all(Val::Px(20.0)),
-                        margin: UiRect::all(Val::Px(20.0)),
-                        size: Size::new(Val::Percent(20.0), Val::Percent(20.0)),
-                        ..default()
+                .spawn_bundle(TextBundle::from_section(
+                    "WitchBrew",
+                    TextStyle {
+                        font: assets.load("Font/DancingScript-VariableFont_wght.ttf"),
+                        font_size: 128.0,
+                        color: Color::rgb(0.2, 0.2, 0.2),
+                    },
+                ))
+                .insert(ScaledFont(128.0));
+            spawn_menu_button(parent, &assets, "Start Game", MenuButton::NewRun);
+            if RunSave::exists() {
+                spawn_menu_button(parent, &assets, "Continue", MenuButton::Continue);
+            }
+            spawn_menu_button(parent, &assets, "Upgrades", MenuButton::Upgrades);
+            spawn_menu_button(parent, &assets, "Settings", MenuButton::Settings);
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "Code By: LogicProjects, Cathanos\nArt By: Madeline Hunt\nMusic By: Dean Manring\nSfx By: gosha05",
+                    TextStyle {
+                        font: assets.load("Font/DancingScript-VariableFont_wght.ttf"),
+                        font_size: 48.0,
+                        color: Color::rgb(0.2, 0.2, 0.2),
+                    },
+                ))
+                .insert(ScaledFont(48.0));
+        });
+}
+
+fn spawn_menu_button(
+    parent: &mut ChildBuilder,
+    assets: &AssetServer,
+    text: &str,
+    button: MenuButton,
+) {
+    parent
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(20.0)),
+                margin: UiRect::all(Val::Px(10.0)),
+                size: Size::new(Val::Percent(20.0), Val::Percent(15.0)),
+                ..default()
+            },
+            color: Color::BLUE.into(),
+            ..default()
+        })
+        .insert(button)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font: assets.load("Font/DancingScript-VariableFont_wght.ttf"),
+                        font_size: 60.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
                     },
-                    color: Color::BLUE.into(),
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn_bundle(TextBundle::from_section(
-                        "Start Game",
-                        TextStyle {
-                            font: assets.load("Font/DancingScript-VariableFont_wght.ttf"),
-                            font_size: 60.0,
-                            color: Color::rgb(0.9, 0.9, 0.9),
-                        },
-                    ));
-                });
-            parent.spawn_bundle(TextBundle::from_section(
-                "Code By: LogicProjects, Cathanos\nArt By: Madeline Hunt\nMusic By: Dean Manring\nSfx By: gosha05",
-                TextStyle {
-                    font: assets.load("Font/DancingScript-VariableFont_wght.ttf"),
-                    font_size: 48.0,
-                    color: Color::rgb(0.2, 0.2, 0.2),
-                },
-            ));
+                ))
+                .insert(ScaledFont(60.0));
         });
 }
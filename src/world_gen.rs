@@ -1,7 +1,7 @@
-use std::time::Duration;
+use std::collections::HashSet;
 
-use bevy_kira_audio::{AudioControl, AudioEasing, AudioTween};
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
 
@@ -11,9 +11,16 @@ impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(create_map)
             .add_event::<ExitEvent>()
+            .add_event::<RoomClearedEvent>()
             .add_system(exit_collision)
             .add_system(fadeout)
-            .add_system_set(SystemSet::on_update(GameState::Main).with_system(load_next_room))
+            .add_system_set(
+                SystemSet::on_update(GameState::Main)
+                    //Same frame as the fade sends the exit, so PostUpdate systems like saving
+                    //always see the new room
+                    .with_system(load_next_room.after(fadeout))
+                    .with_system(mark_room_cleared.after(load_next_room)),
+            )
             .add_system_set(SystemSet::on_enter(GameState::Main).with_system(spawn_start_room));
     }
 }
@@ -21,6 +28,11 @@ impl Plugin for MapPlugin {
 #[derive(Clone)]
 pub struct ExitEvent(ExitDirection);
 
+/// Sent once when the last enemy of a room dies
+pub struct RoomClearedEvent {
+    pub room: (usize, usize),
+}
+
 #[derive(Component)]
 pub struct ScreenFade {
     pub event: ExitEvent,
@@ -58,10 +70,107 @@ fn fadeout(
 #[derive(Component)]
 pub struct RoomMember;
 
+/// Enemies that belong to the current room, what decides if a room is still being fought over
+pub type RoomEnemies<'w, 's> = Query<'w, 's, (), (With<Enemy>, With<RoomMember>)>;
+
+//Exit sensors reach this far past the ends of a doorway
+const DOOR_PADDING: f32 = TILE_SIZE;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct MapDesc {
     x: usize,
     y: usize,
     map: Vec<Vec<MapTile>>,
+    //Rooms roll their enemies from this so a resumed run gets the same rooms
+    seed: u64,
+    //Rooms where every enemy has been killed, these stay empty when revisited
+    #[serde(default)]
+    cleared: HashSet<(usize, usize)>,
+}
+
+impl MapDesc {
+    pub fn new_run() -> Self {
+        //TODO solve bugs with the map generator
+        //Workaround for now is to just hard code a single good map
+        /*     let mut map = generate_map(5, 5);
+
+        while matches!(map, Err(..)) {
+            map = generate_map(5, 5);
+        }
+
+        let map = map.unwrap(); */
+        let map = vec![
+            vec![
+                MapTile::EElbow,
+                MapTile::EPipe,
+                MapTile::WTee,
+                MapTile::NElbow,
+                MapTile::NEnd,
+            ],
+            vec![
+                MapTile::SElbow,
+                MapTile::ETee,
+                MapTile::Cross,
+                MapTile::Cross,
+                MapTile::WElbow,
+            ],
+            vec![
+                MapTile::EEnd,
+                MapTile::NTee,
+                MapTile::NPipe,
+                MapTile::SElbow,
+                MapTile::NElbow,
+            ],
+            vec![
+                MapTile::EElbow,
+                MapTile::Cross,
+                MapTile::WTee,
+                MapTile::ETee,
+                MapTile::NTee,
+            ],
+            vec![
+                MapTile::SElbow,
+                MapTile::WElbow,
+                MapTile::Empty,
+                MapTile::SEnd,
+                MapTile::SEnd,
+            ],
+        ];
+        MapDesc {
+            x: 0,
+            y: 0,
+            map,
+            seed: rand::thread_rng().gen(),
+            cleared: HashSet::default(),
+        }
+    }
+
+    /// Map coordinates of the room the player is in
+    pub fn room(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    pub fn is_start_room(&self) -> bool {
+        self.x == 0 && self.y == 0
+    }
+
+    //The run always starts in the corner at 0, 0
+    pub fn distance_from_start(&self) -> usize {
+        self.x + self.y
+    }
+
+    pub fn is_boss_room(&self) -> bool {
+        self.x == 4 && self.y == 4
+    }
+
+    pub fn is_cleared(&self) -> bool {
+        self.cleared.contains(&(self.x, self.y))
+    }
+
+    //Same numbers every time this room is rolled in this run
+    fn room_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed ^ ((self.x as u64) << 32 | self.y as u64))
+    }
 }
 
 fn load_next_room(
@@ -73,8 +182,7 @@ fn load_next_room(
     images: Res<Assets<Image>>,
     mut player: Query<&mut Transform, With<Player>>,
     mut map: ResMut<MapDesc>,
-    assets: Res<AssetServer>,
-    audio: Res<bevy_kira_audio::prelude::Audio>,
+    enemy_defs: Res<Assets<EnemyDef>>,
 ) {
     for event in event.iter() {
         for ent in &to_despawn {
@@ -88,65 +196,136 @@ fn load_next_room(
         }
 
         let mut player = player.single_mut();
-        let bounds = 28.0 * 0.8 * 64.0;
-        match event.0 {
-            ExitDirection::North => player.translation.y = -bounds,
-            ExitDirection::South => player.translation.y = bounds,
-            ExitDirection::East => player.translation.x = -bounds,
-            ExitDirection::West => player.translation.x = bounds,
-        }
-        let bounds = bounds * 0.7;
-
-        if map.x == 4 && map.y == 4 {
-            info!("Boss Room");
-            let tween = AudioTween::new(Duration::from_millis(400), AudioEasing::Linear);
-            audio.stop().fade_out(tween);
-            audio.play(assets.load("Music/Boss.wav")).looped();
-            let pos = Vec3::new(200., 200.0, 0.0);
-            spawn_boss(&mut commands, &game_assets, pos);
-        }
-        let mut frog_pos = Vec::default();
-        //Spawn some frogs
-        for i in 0..rand::thread_rng().gen_range(3..7) {
-            frog_pos.push(Vec3::new(
-                rand::thread_rng().gen_range(-bounds..bounds),
-                rand::thread_rng().gen_range(-bounds..bounds),
-                0.0,
-            ));
-        }
-        let mut bat_pos = Vec::default();
-        //Spawn some bats
-        for i in 0..rand::thread_rng().gen_range(3..6) {
-            bat_pos.push(Vec3::new(
-                rand::thread_rng().gen_range(-bounds..bounds),
-                rand::thread_rng().gen_range(-bounds..bounds),
-                0.0,
-            ));
+        let image = room_image(&bg_assets, &images, &map);
+        let size = RoomSize::from_image(image);
+        let doors = find_doors(image, &size);
+        match doors
+            .iter()
+            .find(|door| door.direction == event.0.opposite())
+        {
+            Some(door) => {
+                let entry = door.entry_point();
+                player.translation.x = entry.x;
+                player.translation.y = entry.y;
+            }
+            //No matching doorway, fall back to the middle of the opposite wall
+            None => {
+                let entry = size.entry_distance();
+                match event.0 {
+                    ExitDirection::North => player.translation.y = -entry.y,
+                    ExitDirection::South => player.translation.y = entry.y,
+                    ExitDirection::East => player.translation.x = -entry.x,
+                    ExitDirection::West => player.translation.x = entry.x,
+                }
+            }
         }
-
-        spawn_room(
+        populate_room(
             &mut commands,
+            &game_assets,
+            &enemy_defs,
             &bg_assets,
             &images,
             &map,
-            &mut frog_pos,
-            &mut bat_pos,
         );
+    }
+}
 
-        for pos in frog_pos {
-            spawn_frog(&mut commands, &game_assets, pos);
+/// Builds the room the map is currently on and fills it with enemies, unless they were already
+/// killed
+fn populate_room(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    enemy_defs: &Assets<EnemyDef>,
+    bg_assets: &Res<BackgroundAssets>,
+    images: &Res<Assets<Image>>,
+    map: &MapDesc,
+) {
+    if map.is_cleared() {
+        spawn_room(commands, bg_assets, images, map, &mut Vec::default());
+        return;
+    }
+    if map.is_start_room() {
+        spawn_room(commands, bg_assets, images, map, &mut Vec::default());
+        let pos = Vec3::new(200., 200.0, 0.0);
+        if let Some(frog) = find_enemy_def(game_assets, enemy_defs, "Frog") {
+            spawn_enemy(commands, frog, pos, &[]);
         }
-        for pos in bat_pos {
-            spawn_bat(&mut commands, &game_assets, pos);
+        return;
+    }
+
+    let bounds = room_size(bg_assets, images, map).spawn_area();
+    let boss_room = map.is_boss_room();
+    if boss_room {
+        info!("Boss Room");
+    }
+    let mut rng = map.room_rng();
+    let mut enemy_pos = Vec::default();
+    for handle in &game_assets.enemies {
+        let def = match enemy_defs.get(handle) {
+            Some(def) => def,
+            None => continue,
+        };
+        match def.spawn {
+            SpawnRule::Room { min, max } => {
+                for _ in 0..rng.gen_range(min..=max) {
+                    enemy_pos.push((
+                        handle.clone(),
+                        Vec3::new(
+                            rng.gen_range(-bounds.x..bounds.x),
+                            rng.gen_range(-bounds.y..bounds.y),
+                            0.0,
+                        ),
+                    ));
+                }
+            }
+            SpawnRule::Boss => {
+                if boss_room {
+                    spawn_enemy(commands, def, Vec3::new(200., 200.0, 0.0), &[]);
+                }
+            }
+        }
+    }
+
+    spawn_room(commands, bg_assets, images, map, &mut enemy_pos);
+
+    for (handle, pos) in enemy_pos {
+        if let Some(def) = enemy_defs.get(&handle) {
+            let elite = roll_elite(def, map, &mut rng);
+            spawn_enemy(commands, def, pos, &elite);
         }
     }
 }
 
+/// Remembers a room as cleared once every enemy in it is gone
+fn mark_room_cleared(
+    mut map: ResMut<MapDesc>,
+    enemies: RoomEnemies,
+    mut seen_enemies: Local<bool>,
+    mut cleared: EventWriter<RoomClearedEvent>,
+) {
+    //Room changed, enemies from the last one are still waiting to despawn so skip this frame
+    if map.is_changed() {
+        *seen_enemies = false;
+        return;
+    }
+    if map.is_cleared() {
+        return;
+    }
+    if !enemies.is_empty() {
+        *seen_enemies = true;
+    } else if *seen_enemies {
+        *seen_enemies = false;
+        let room = (map.x, map.y);
+        map.cleared.insert(room);
+        cleared.send(RoomClearedEvent { room });
+    }
+}
+
 fn exit_collision(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
     fade: Query<&ScreenFade>,
-    exits: Query<&ExitDirection>,
+    exits: Query<&ExitDirection, Without<Locked>>,
     player: Query<(), With<Player>>,
 ) {
     if fade.iter().count() != 0 {
@@ -214,84 +393,35 @@ fn exit_collision(
 }
 
 pub fn create_map(mut commands: Commands) {
-    //TODO solve bugs with the map generator
-    //Workaround for now is to just hard code a single good map
-    /*     let mut map = generate_map(5, 5);
-
-    while matches!(map, Err(..)) {
-        map = generate_map(5, 5);
-    }
-
-    let map = map.unwrap(); */
-    let map = vec![
-        vec![
-            MapTile::EElbow,
-            MapTile::EPipe,
-            MapTile::WTee,
-            MapTile::NElbow,
-            MapTile::NEnd,
-        ],
-        vec![
-            MapTile::SElbow,
-            MapTile::ETee,
-            MapTile::Cross,
-            MapTile::Cross,
-            MapTile::WElbow,
-        ],
-        vec![
-            MapTile::EEnd,
-            MapTile::NTee,
-            MapTile::NPipe,
-            MapTile::SElbow,
-            MapTile::NElbow,
-        ],
-        vec![
-            MapTile::EElbow,
-            MapTile::Cross,
-            MapTile::WTee,
-            MapTile::ETee,
-            MapTile::NTee,
-        ],
-        vec![
-            MapTile::SElbow,
-            MapTile::WElbow,
-            MapTile::Empty,
-            MapTile::SEnd,
-            MapTile::SEnd,
-        ],
-    ];
-    commands.insert_resource(MapDesc { x: 0, y: 0, map });
+    commands.insert_resource(MapDesc::new_run());
 }
+
 fn spawn_start_room(
     mut commands: Commands,
     assets: Res<BackgroundAssets>,
     images: Res<Assets<Image>>,
     game_assets: Res<GameAssets>,
+    enemy_defs: Res<Assets<EnemyDef>>,
     map: Res<MapDesc>,
 ) {
-    spawn_room(
+    //Usually the start room, but a continued run picks up wherever it was saved
+    populate_room(
         &mut commands,
+        &game_assets,
+        &enemy_defs,
         &assets,
         &images,
         &map,
-        &mut Vec::default(),
-        &mut Vec::default(),
     );
-    let pos = Vec3::new(200., 200.0, 0.0);
-    spawn_frog(&mut commands, &game_assets, pos);
 }
 
-fn spawn_room(
-    commands: &mut Commands,
-    assets: &Res<BackgroundAssets>,
-    images: &Res<Assets<Image>>,
+fn room_image<'a>(
+    assets: &BackgroundAssets,
+    images: &'a Assets<Image>,
     map: &MapDesc,
-    frogs_to_check: &mut Vec<Vec3>,
-    bats_to_check: &mut Vec<Vec3>,
-) {
+) -> &'a Image {
     let room = map.map[map.y][map.x];
-    println!("Loading {:?}", room);
-    let image = match room {
+    match room {
         MapTile::NPipe => images.get(&assets.npipe.clone()).unwrap(),
         MapTile::EPipe => images.get(&assets.epipe.clone()).unwrap(),
         MapTile::NElbow => images.get(&assets.nelbow.clone()).unwrap(),
@@ -308,60 +438,60 @@ fn spawn_room(
         MapTile::WTee => images.get(&assets.wtee.clone()).unwrap(),
         MapTile::Cross => images.get(&assets.cross.clone()).unwrap(),
         MapTile::Empty => images.get(&assets.empty.clone()).unwrap(),
-    };
+    }
+}
+
+fn room_size(assets: &BackgroundAssets, images: &Assets<Image>, map: &MapDesc) -> RoomSize {
+    RoomSize::from_image(room_image(assets, images, map))
+}
+
+fn spawn_room(
+    commands: &mut Commands,
+    assets: &Res<BackgroundAssets>,
+    images: &Res<Assets<Image>>,
+    map: &MapDesc,
+    enemies_to_check: &mut Vec<(Handle<EnemyDef>, Vec3)>,
+) {
+    println!("Loading {:?}", map.map[map.y][map.x]);
+    let image = room_image(assets, images, map);
     assert!(image.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb);
 
-    let width = image.size().x as usize;
-    let height = image.size().y as usize;
+    let size = RoomSize::from_image(image);
+    let width = size.width;
+    let height = size.height;
 
-    let pixel_size = 0.8;
-    let tile_size = 64.0;
-    let offset = Vec3::new(
-        -32.0 * tile_size * pixel_size,
-        32.0 * tile_size * pixel_size,
-        0.0,
-    );
+    let offset = size.origin().extend(0.0);
 
     let mut ids = Vec::default();
+    let mut walkable = Vec::with_capacity(width * height);
     for y in 0..height {
         for x in 0..width {
-            let index = 4 * (x + y * width);
-            let r = image.data[index];
-            let g = image.data[index + 1];
-            let b = image.data[index + 2];
+            let (r, g, b) = pixel(image, width, x, y);
             let index = color_to_tile_index(r, g, b);
+            walkable.push(index == 0);
             let id = commands
                 .spawn_bundle(SpriteSheetBundle {
                     sprite: TextureAtlasSprite { index, ..default() },
                     texture_atlas: assets.tileset.clone(),
                     transform: Transform::from_translation(
-                        Vec3::new(
-                            x as f32 * tile_size * pixel_size,
-                            y as f32 * -tile_size * pixel_size,
-                            0.0,
-                        ) + offset,
+                        Vec3::new(x as f32 * TILE_SIZE, y as f32 * -TILE_SIZE, 0.0) + offset,
                     )
-                    .with_scale(Vec3::splat(pixel_size)),
+                    .with_scale(Vec3::splat(TILE_SCALE)),
                     ..default()
                 })
                 .insert(RoomMember)
                 .id();
             ids.push(id);
             if index == 1 {
-                let x = x as f32 * tile_size * pixel_size + offset.x;
-                let y = y as f32 * -tile_size * pixel_size + offset.y;
-                frogs_to_check.retain(|pos| {
-                    !((pos.x - x).abs() < tile_size * pixel_size * 1.5
-                        && (pos.y - y).abs() < tile_size * pixel_size * 1.5)
-                });
-                bats_to_check.retain(|pos| {
-                    !((pos.x - x).abs() < tile_size * pixel_size * 1.5
-                        && (pos.y - y).abs() < tile_size * pixel_size * 1.5)
+                let x = x as f32 * TILE_SIZE + offset.x;
+                let y = y as f32 * -TILE_SIZE + offset.y;
+                enemies_to_check.retain(|(_, pos)| {
+                    !((pos.x - x).abs() < TILE_SIZE * 1.5 && (pos.y - y).abs() < TILE_SIZE * 1.5)
                 });
                 commands
                     .entity(id)
                     .insert(CollisionShape::Cuboid {
-                        half_extends: Vec2::splat(tile_size * pixel_size / 2.0).extend(1.0),
+                        half_extends: Vec2::splat(TILE_SIZE / 2.0).extend(1.0),
                         border_radius: None,
                     })
                     .insert(
@@ -376,11 +506,58 @@ fn spawn_room(
         .spawn_bundle(SpatialBundle::default())
         .insert(RoomMember)
         .push_children(&ids);
+    spawn_room_exits(commands, &find_doors(image, &size));
+    commands.insert_resource(NavGrid::new(
+        width,
+        height,
+        walkable,
+        TILE_SIZE,
+        offset.truncate(),
+    ));
+    commands.insert_resource(size);
+}
+
+/// Sensors across each doorway, walking into one moves to the next room over
+fn spawn_room_exits(commands: &mut Commands, doors: &[Door]) {
+    for door in doors {
+        //Long side runs along the wall
+        let half_width = door.half_width + DOOR_PADDING;
+        let half_extends = match door.direction {
+            ExitDirection::West | ExitDirection::East => Vec2::new(50.0, half_width),
+            ExitDirection::North | ExitDirection::South => Vec2::new(half_width, 50.0),
+        };
+        commands
+            .spawn_bundle(SpatialBundle {
+                transform: Transform::from_translation(door.position.extend(0.0)),
+                ..default()
+            })
+            .insert(CollisionShape::Cuboid {
+                half_extends: half_extends.extend(1.0),
+                border_radius: None,
+            })
+            .insert(CollisionLayers::all_masks::<PhysicLayer>().with_group(PhysicLayer::World))
+            .insert(RotationConstraints::lock())
+            .insert(door.direction)
+            .insert(RigidBody::Sensor)
+            .insert(RoomMember)
+            .with_children(|parent| {
+                let gate = match door.direction {
+                    ExitDirection::West | ExitDirection::East => {
+                        Vec2::new(TILE_SIZE, door.half_width * 2.0)
+                    }
+                    ExitDirection::North | ExitDirection::South => {
+                        Vec2::new(door.half_width * 2.0, TILE_SIZE)
+                    }
+                };
+                spawn_exit_gate(parent, gate);
+            });
+    }
 }
 
 fn color_to_tile_index(r: u8, g: u8, b: u8) -> usize {
     match (r, g, b) {
         (11, 61, 38) | (16, 121, 15) | (76, 90, 84) => 0,
+        rgb if rgb == DOOR_MARKER => 0,
         _ => 1, //_ => unreachable!("Unknown Color {:?}", (r, g, b)),
     }
 }
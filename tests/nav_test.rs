@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use potion_roguelike::map::nav::*;
+
+    //# is wall, anything else is floor
+    fn grid_from(rows: &[&str]) -> NavGrid {
+        let width = rows[0].len();
+        let walkable = rows
+            .iter()
+            .flat_map(|row| row.chars().map(|c| c != '#'))
+            .collect();
+        NavGrid::new(width, rows.len(), walkable, 10.0, Vec2::ZERO)
+    }
+
+    #[test]
+    fn test_cell_round_trip() {
+        let grid = grid_from(&["....", "....", "...."]);
+        let cell = IVec2::new(3, 2);
+        assert_eq!(grid.world_to_cell(grid.cell_to_world(cell)), cell);
+        assert_eq!(grid.cell_to_world(cell), Vec2::new(30.0, -20.0));
+    }
+
+    #[test]
+    fn test_line_of_sight_blocked_by_wall() {
+        let grid = grid_from(&[".....", "..#..", "....."]);
+        let left = grid.cell_to_world(IVec2::new(0, 1));
+        let right = grid.cell_to_world(IVec2::new(4, 1));
+        let top_right = grid.cell_to_world(IVec2::new(4, 0));
+        assert!(!grid.line_of_sight(left, right));
+        assert!(grid.line_of_sight(grid.cell_to_world(IVec2::new(0, 0)), top_right));
+    }
+
+    #[test]
+    fn test_path_goes_around_wall() {
+        let grid = grid_from(&[".....", ".###.", "....."]);
+        let start = grid.cell_to_world(IVec2::new(0, 2));
+        let end = grid.cell_to_world(IVec2::new(4, 0));
+        let path = grid.find_path(start, end).unwrap();
+        assert_eq!(*path.last().unwrap(), end);
+        for point in &path {
+            assert!(grid.is_walkable(grid.world_to_cell(*point)));
+        }
+    }
+
+    #[test]
+    fn test_no_path_through_solid_wall() {
+        let grid = grid_from(&["..#..", "..#..", "..#.."]);
+        let start = grid.cell_to_world(IVec2::new(0, 0));
+        let end = grid.cell_to_world(IVec2::new(4, 2));
+        assert!(grid.find_path(start, end).is_none());
+    }
+}
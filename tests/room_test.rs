@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        prelude::*,
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+    use potion_roguelike::map::room::*;
+
+    //One row per line, D is a door marker and anything else is floor
+    fn room_from(rows: &str) -> (Image, RoomSize) {
+        let rows: Vec<&str> = rows.split_whitespace().collect();
+        let width = rows[0].len();
+        let data = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .flat_map(|c| match c {
+                'D' => [DOOR_MARKER.0, DOOR_MARKER.1, DOOR_MARKER.2, 255],
+                _ => [11, 61, 38, 255],
+            })
+            .collect();
+        let image = Image::new(
+            Extent3d {
+                width: width as u32,
+                height: rows.len() as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let size = RoomSize::from_image(&image);
+        (image, size)
+    }
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.abs_diff_eq(b, 0.001), "{a} != {b}");
+    }
+
+    fn door(doors: &[Door], direction: ExitDirection) -> Door {
+        *doors
+            .iter()
+            .find(|door| door.direction == direction)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_markers_make_one_door() {
+        let (image, size) = room_from(
+            "........
+             ...DD...
+             ........
+             ........
+             ........
+             ........
+             ........
+             ........",
+        );
+        let doors = find_doors(&image, &size);
+        assert_eq!(doors.len(), 1);
+        let north = door(&doors, ExitDirection::North);
+        //Halfway between the two marked tiles, one row in from the top
+        assert_close(north.position, Vec2::new(-0.5, 3.0) * TILE_SIZE);
+        assert!((north.half_width - TILE_SIZE).abs() < 0.001);
+        assert_close(north.entry_point(), Vec2::new(-0.5, 0.0) * TILE_SIZE);
+    }
+
+    #[test]
+    fn test_markers_grouped_by_nearest_wall() {
+        let (image, size) = room_from(
+            "........
+             ........
+             ........
+             .D......
+             .D....D.
+             ......D.
+             ........
+             ...D....",
+        );
+        let doors = find_doors(&image, &size);
+        assert_eq!(doors.len(), 3);
+        let west = door(&doors, ExitDirection::West);
+        let east = door(&doors, ExitDirection::East);
+        let south = door(&doors, ExitDirection::South);
+        assert_close(west.position, Vec2::new(-3.0, 0.5) * TILE_SIZE);
+        assert_close(east.position, Vec2::new(2.0, -0.5) * TILE_SIZE);
+        assert_close(south.position, Vec2::new(-1.0, -3.0) * TILE_SIZE);
+        //Doors run along their wall
+        assert!((west.half_width - TILE_SIZE).abs() < 0.001);
+        assert!((south.half_width - TILE_SIZE / 2.0).abs() < 0.001);
+        //Entries are inside the room
+        assert!(west.entry_point().x > west.position.x);
+        assert!(east.entry_point().x < east.position.x);
+        assert!(south.entry_point().y > south.position.y);
+    }
+
+    #[test]
+    fn test_no_markers_falls_back_to_every_wall() {
+        let (image, size) = room_from(&["......"; 6].join("\n"));
+        let doors = find_doors(&image, &size);
+        assert_eq!(doors.len(), 4);
+        let exit = size.exit_distance();
+        assert_close(
+            door(&doors, ExitDirection::North).position,
+            Vec2::new(0.0, exit.y),
+        );
+        assert_close(
+            door(&doors, ExitDirection::South).position,
+            Vec2::new(0.0, -exit.y),
+        );
+        assert_close(
+            door(&doors, ExitDirection::East).position,
+            Vec2::new(exit.x, 0.0),
+        );
+        assert_close(
+            door(&doors, ExitDirection::West).position,
+            Vec2::new(-exit.x, 0.0),
+        );
+    }
+}